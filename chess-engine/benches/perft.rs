@@ -0,0 +1,117 @@
+use chess_engine::bitboard::attacks;
+use chess_engine::board::{Board, STARTING_FEN};
+use chess_engine::perft::perft;
+use std::hint::black_box;
+use std::time::Instant;
+
+const POSITIONS: [(&str, &str, u32); 4] = [
+    ("startpos", STARTING_FEN, 5),
+    (
+        "kiwipete",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        4,
+    ),
+    ("position 3", "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 5),
+    (
+        "position 5",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        4,
+    ),
+];
+
+// Random occupancies with roughly a quarter of the squares filled, like a
+// middlegame board.
+fn occupancies(count: usize) -> Vec<u64> {
+    let mut state = 0x9E37_79B9_7F4A_7C15u64;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    (0..count).map(|_| next() & next()).collect()
+}
+
+// Magic lookups against the ray loops the move generator used before them.
+fn compare_slider_attacks(lookups: usize) {
+    let boards = occupancies(lookups / 64);
+
+    let time = |attacks: &dyn Fn(usize, u64) -> u64| {
+        let start = Instant::now();
+        let mut checksum = 0u64;
+        for &occupancy in &boards {
+            for square in 0..64 {
+                checksum ^= attacks(black_box(square), black_box(occupancy));
+            }
+        }
+        black_box(checksum);
+        start.elapsed().as_secs_f64()
+    };
+
+    let ray_walk = time(&|square, occupancy| {
+        attacks::bishop_attacks_ray_walk(square, occupancy)
+            | attacks::rook_attacks_ray_walk(square, occupancy)
+    });
+    let magic = time(&attacks::queen_attacks);
+
+    println!(
+        "{:<12} ray walk {:>8.3}s  magic {:>8.3}s  {:>5.1}x faster",
+        "sliders",
+        ray_walk,
+        magic,
+        ray_walk / magic.max(1e-9)
+    );
+}
+
+fn main() {
+    // `cargo bench` passes --bench; `cargo test --all-targets` only smoke-tests this target.
+    let benchmarking = std::env::args().any(|arg| arg == "--bench");
+
+    attacks::init();
+    println!(
+        "perft with {} slider attacks",
+        if cfg!(feature = "ray-walk-sliders") {
+            "ray-walk"
+        } else {
+            "magic"
+        }
+    );
+
+    let mut total_nodes = 0u64;
+    let mut total_seconds = 0f64;
+
+    for (name, fen, depth) in POSITIONS {
+        let depth = if benchmarking { depth } else { 2 };
+        let mut board = Board::from_fen(fen).unwrap();
+
+        let start = Instant::now();
+        let nodes = perft(&mut board, depth);
+        let seconds = start.elapsed().as_secs_f64();
+
+        total_nodes += nodes;
+        total_seconds += seconds;
+
+        println!(
+            "{:<12} depth {}  {:>10} nodes  {:>8.3}s  {:>7.2} Mnps",
+            name,
+            depth,
+            nodes,
+            seconds,
+            nodes as f64 / seconds.max(1e-9) / 1e6
+        );
+    }
+
+    println!(
+        "{:<12}          {:>10} nodes  {:>8.3}s  {:>7.2} Mnps",
+        "total",
+        total_nodes,
+        total_seconds,
+        total_nodes as f64 / total_seconds.max(1e-9) / 1e6
+    );
+
+    // With the feature on, both sides of the comparison would be ray walks.
+    if !cfg!(feature = "ray-walk-sliders") {
+        println!();
+        compare_slider_attacks(if benchmarking { 20_000_000 } else { 64_000 });
+    }
+}
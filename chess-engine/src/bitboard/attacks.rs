@@ -0,0 +1,320 @@
+use crate::pieces::piece_type::Color;
+use std::sync::OnceLock;
+
+const KNIGHT_OFFSETS: [(i32, i32); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+const KING_OFFSETS: [(i32, i32); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+const WHITE_PAWN_OFFSETS: [(i32, i32); 2] = [(-1, 1), (1, 1)];
+const BLACK_PAWN_OFFSETS: [(i32, i32); 2] = [(-1, -1), (1, -1)];
+
+const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+const ROOK_DIRECTIONS: [(i32, i32); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+
+static KNIGHT_ATTACKS: [u64; 64] = generate_step_attacks(&KNIGHT_OFFSETS);
+static KING_ATTACKS: [u64; 64] = generate_step_attacks(&KING_OFFSETS);
+static PAWN_ATTACKS: [[u64; 64]; 2] = [
+    generate_step_attacks(&WHITE_PAWN_OFFSETS),
+    generate_step_attacks(&BLACK_PAWN_OFFSETS),
+];
+
+static SLIDER_TABLES: OnceLock<SliderTables> = OnceLock::new();
+
+const fn generate_step_attacks(offsets: &[(i32, i32)]) -> [u64; 64] {
+    let mut table = [0u64; 64];
+
+    let mut square = 0;
+    while square < 64 {
+        let file = (square % 8) as i32;
+        let rank = (square / 8) as i32;
+
+        let mut i = 0;
+        while i < offsets.len() {
+            let new_file = file + offsets[i].0;
+            let new_rank = rank + offsets[i].1;
+            if new_file >= 0 && new_file < 8 && new_rank >= 0 && new_rank < 8 {
+                table[square] |= 1u64 << (new_rank * 8 + new_file);
+            }
+            i += 1;
+        }
+
+        square += 1;
+    }
+
+    table
+}
+
+// Slider tables are built on first use; call this up front to keep that cost
+// out of anything being timed.
+pub fn init() {
+    slider_tables();
+}
+
+pub fn knight_attacks(square: usize) -> u64 {
+    KNIGHT_ATTACKS[square]
+}
+
+pub fn king_attacks(square: usize) -> u64 {
+    KING_ATTACKS[square]
+}
+
+pub fn pawn_attacks(color: Color, square: usize) -> u64 {
+    PAWN_ATTACKS[color as usize][square]
+}
+
+pub fn bishop_attacks(square: usize, occupancy: u64) -> u64 {
+    if cfg!(feature = "ray-walk-sliders") {
+        return bishop_attacks_ray_walk(square, occupancy);
+    }
+    let tables = slider_tables();
+    tables.bishop[square].lookup(&tables.attacks, occupancy)
+}
+
+pub fn rook_attacks(square: usize, occupancy: u64) -> u64 {
+    if cfg!(feature = "ray-walk-sliders") {
+        return rook_attacks_ray_walk(square, occupancy);
+    }
+    let tables = slider_tables();
+    tables.rook[square].lookup(&tables.attacks, occupancy)
+}
+
+pub fn queen_attacks(square: usize, occupancy: u64) -> u64 {
+    bishop_attacks(square, occupancy) | rook_attacks(square, occupancy)
+}
+
+// The ray walk the magic tables are built from, kept public so benchmarks
+// can compare the lookups against it.
+pub fn bishop_attacks_ray_walk(square: usize, occupancy: u64) -> u64 {
+    slider_attacks_slow(square, occupancy, &BISHOP_DIRECTIONS)
+}
+
+pub fn rook_attacks_ray_walk(square: usize, occupancy: u64) -> u64 {
+    slider_attacks_slow(square, occupancy, &ROOK_DIRECTIONS)
+}
+
+#[derive(Clone, Copy, Default)]
+struct Magic {
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    fn index(&self, occupancy: u64) -> usize {
+        ((occupancy & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+
+    fn lookup(&self, attacks: &[u64], occupancy: u64) -> u64 {
+        attacks[self.offset + self.index(occupancy)]
+    }
+}
+
+struct SliderTables {
+    bishop: [Magic; 64],
+    rook: [Magic; 64],
+    attacks: Vec<u64>,
+}
+
+fn slider_tables() -> &'static SliderTables {
+    SLIDER_TABLES.get_or_init(|| {
+        let mut attacks = Vec::new();
+        let bishop = find_magics(&BISHOP_DIRECTIONS, &mut attacks);
+        let rook = find_magics(&ROOK_DIRECTIONS, &mut attacks);
+
+        SliderTables {
+            bishop,
+            rook,
+            attacks,
+        }
+    })
+}
+
+// Per-rank seeds known to converge quickly, so building the tables stays in the
+// low milliseconds and the result is identical on every run.
+const MAGIC_SEEDS: [u64; 8] = [728, 10316, 55013, 32803, 12281, 15100, 16645, 255];
+
+fn find_magics(directions: &[(i32, i32)], attacks: &mut Vec<u64>) -> [Magic; 64] {
+    let mut magics = [Magic::default(); 64];
+
+    for (square, entry) in magics.iter_mut().enumerate() {
+        let mask = relevant_occupancy_mask(square, directions);
+        let bits = mask.count_ones();
+        let size = 1usize << bits;
+
+        let mut occupancies = Vec::with_capacity(size);
+        let mut references = Vec::with_capacity(size);
+        let mut subset = 0u64;
+        loop {
+            occupancies.push(subset);
+            references.push(slider_attacks_slow(square, subset, directions));
+            subset = subset.wrapping_sub(mask) & mask;
+            if subset == 0 {
+                break;
+            }
+        }
+
+        let mut rng = MAGIC_SEEDS[square / 8];
+        let offset = attacks.len();
+        attacks.resize(offset + size, 0);
+        let mut epochs = vec![0u32; size];
+        let mut epoch = 0u32;
+
+        'search: loop {
+            let magic = sparse_random(&mut rng);
+            if (mask.wrapping_mul(magic) >> 56).count_ones() < 6 {
+                continue;
+            }
+
+            let candidate = Magic {
+                mask,
+                magic,
+                shift: 64 - bits,
+                offset,
+            };
+            epoch += 1;
+
+            for (&occupancy, &reference) in occupancies.iter().zip(&references) {
+                let index = candidate.index(occupancy);
+                if epochs[index] < epoch {
+                    epochs[index] = epoch;
+                    attacks[offset + index] = reference;
+                } else if attacks[offset + index] != reference {
+                    continue 'search;
+                }
+            }
+
+            *entry = candidate;
+            break;
+        }
+    }
+
+    magics
+}
+
+fn relevant_occupancy_mask(square: usize, directions: &[(i32, i32)]) -> u64 {
+    let file = (square % 8) as i32;
+    let rank = (square / 8) as i32;
+    let mut mask = 0u64;
+
+    for &(file_delta, rank_delta) in directions {
+        let mut curr_file = file + file_delta;
+        let mut curr_rank = rank + rank_delta;
+
+        // The last square of each ray is never a blocker worth indexing.
+        while (0..8).contains(&(curr_file + file_delta))
+            && (0..8).contains(&(curr_rank + rank_delta))
+        {
+            mask |= 1u64 << (curr_rank * 8 + curr_file);
+            curr_file += file_delta;
+            curr_rank += rank_delta;
+        }
+    }
+
+    mask
+}
+
+fn slider_attacks_slow(square: usize, occupancy: u64, directions: &[(i32, i32)]) -> u64 {
+    let file = (square % 8) as i32;
+    let rank = (square / 8) as i32;
+    let mut attacks = 0u64;
+
+    for &(file_delta, rank_delta) in directions {
+        let mut curr_file = file + file_delta;
+        let mut curr_rank = rank + rank_delta;
+
+        while (0..8).contains(&curr_file) && (0..8).contains(&curr_rank) {
+            let target = 1u64 << (curr_rank * 8 + curr_file);
+            attacks |= target;
+            if occupancy & target != 0 {
+                break;
+            }
+            curr_file += file_delta;
+            curr_rank += rank_delta;
+        }
+    }
+
+    attacks
+}
+
+fn sparse_random(state: &mut u64) -> u64 {
+    let mut next = || {
+        *state ^= *state >> 12;
+        *state ^= *state << 25;
+        *state ^= *state >> 27;
+        state.wrapping_mul(2_685_821_657_736_338_717)
+    };
+
+    next() & next() & next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_knight_attacks() {
+        assert_eq!(knight_attacks(0), (1u64 << 10) | (1u64 << 17));
+        assert_eq!(knight_attacks(27).count_ones(), 8);
+    }
+
+    #[test]
+    fn test_king_attacks() {
+        assert_eq!(king_attacks(0), (1u64 << 1) | (1u64 << 8) | (1u64 << 9));
+        assert_eq!(king_attacks(63).count_ones(), 3);
+        assert_eq!(king_attacks(27).count_ones(), 8);
+    }
+
+    #[test]
+    fn test_pawn_attacks() {
+        assert_eq!(pawn_attacks(Color::White, 12), (1u64 << 19) | (1u64 << 21));
+        assert_eq!(pawn_attacks(Color::White, 8), 1u64 << 17);
+        assert_eq!(pawn_attacks(Color::Black, 52), (1u64 << 43) | (1u64 << 45));
+        assert_eq!(pawn_attacks(Color::Black, 55), 1u64 << 46);
+    }
+
+    #[test]
+    fn test_slider_lookups_match_ray_walk() {
+        let mut rng = 0x1234_5678_9ABC_DEF1u64;
+
+        for square in 0..64 {
+            for _ in 0..64 {
+                let occupancy = sparse_random(&mut rng) | sparse_random(&mut rng);
+
+                assert_eq!(
+                    bishop_attacks(square, occupancy),
+                    slider_attacks_slow(square, occupancy, &BISHOP_DIRECTIONS)
+                );
+                assert_eq!(
+                    rook_attacks(square, occupancy),
+                    slider_attacks_slow(square, occupancy, &ROOK_DIRECTIONS)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_queen_attacks_on_empty_board() {
+        assert_eq!(queen_attacks(27, 0).count_ones(), 27);
+        assert_eq!(rook_attacks(0, 0).count_ones(), 14);
+        assert_eq!(bishop_attacks(0, 0).count_ones(), 7);
+    }
+}
@@ -1,2 +1,215 @@
+pub mod attacks;
 pub mod constants;
 pub mod operations;
+pub mod square;
+
+pub use square::{ParseSquareError, Square};
+
+use operations::{
+    bitboard_to_square, clear_bit, count_bits, get_lsb_index, set_bit, square_to_bitboard, test_bit,
+};
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+    pub const EMPTY: Self = Self(constants::EMPTY);
+    pub const FULL: Self = Self(constants::UNIVERSE);
+
+    pub fn from_square(square: Square) -> Self {
+        Self(square_to_bitboard(square.index()))
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(self, square: Square) -> bool {
+        test_bit(self.0, square.index())
+    }
+
+    pub fn insert(&mut self, square: Square) {
+        set_bit(&mut self.0, square.index());
+    }
+
+    pub fn remove(&mut self, square: Square) {
+        clear_bit(&mut self.0, square.index());
+    }
+
+    pub fn count(self) -> u32 {
+        count_bits(self.0)
+    }
+
+    pub fn lsb(self) -> Option<Square> {
+        get_lsb_index(self.0).map(Square::new)
+    }
+
+    pub fn pop_lsb(&mut self) -> Option<Square> {
+        let square = self.lsb()?;
+        self.remove(square);
+        Some(square)
+    }
+
+    pub fn to_square(self) -> Option<Square> {
+        bitboard_to_square(self.0).map(Square::new)
+    }
+
+    pub fn iter(self) -> Squares {
+        Squares(self)
+    }
+}
+
+impl From<u64> for Bitboard {
+    fn from(bits: u64) -> Self {
+        Self(bits)
+    }
+}
+
+impl From<Bitboard> for u64 {
+    fn from(bitboard: Bitboard) -> Self {
+        bitboard.0
+    }
+}
+
+impl From<Square> for Bitboard {
+    fn from(square: Square) -> Self {
+        Self::from_square(square)
+    }
+}
+
+impl FromIterator<Square> for Bitboard {
+    fn from_iter<I: IntoIterator<Item = Square>>(iter: I) -> Self {
+        let mut bitboard = Self::EMPTY;
+        for square in iter {
+            bitboard.insert(square);
+        }
+        bitboard
+    }
+}
+
+impl IntoIterator for Bitboard {
+    type Item = Square;
+    type IntoIter = Squares;
+
+    fn into_iter(self) -> Squares {
+        self.iter()
+    }
+}
+
+pub struct Squares(Bitboard);
+
+impl Iterator for Squares {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Square> {
+        self.0.pop_lsb()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.0.count() as usize;
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for Squares {}
+
+impl BitAnd for Bitboard {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for Bitboard {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Bitboard {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitXor for Bitboard {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self {
+        Self(self.0 ^ rhs.0)
+    }
+}
+
+impl BitXorAssign for Bitboard {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.0 ^= rhs.0;
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_contains_remove() {
+        let mut bitboard = Bitboard::EMPTY;
+        bitboard.insert(Square::E4);
+
+        assert!(bitboard.contains(Square::E4));
+        assert!(!bitboard.contains(Square::E5));
+        assert_eq!(bitboard.to_square(), Some(Square::E4));
+
+        bitboard.remove(Square::E4);
+        assert!(bitboard.is_empty());
+    }
+
+    #[test]
+    fn test_iterates_squares_in_ascending_order() {
+        let bitboard = Bitboard(constants::RANK_1 & (constants::FILE_A | constants::FILE_H));
+        let squares: Vec<Square> = bitboard.iter().collect();
+
+        assert_eq!(squares, vec![Square::A1, Square::H1]);
+        assert_eq!(bitboard.iter().len(), 2);
+        assert_eq!(Bitboard::FULL.iter().count(), 64);
+        assert_eq!(Bitboard::EMPTY.iter().next(), None);
+    }
+
+    #[test]
+    fn test_collects_from_squares() {
+        let bitboard: Bitboard = [Square::B1, Square::G1].into_iter().collect();
+
+        assert_eq!(bitboard, Bitboard(0x42));
+        assert_eq!(bitboard.count(), 2);
+        assert_eq!(bitboard.to_square(), None);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = Bitboard(0b1100);
+        let b = Bitboard(0b1010);
+
+        assert_eq!(a & b, Bitboard(0b1000));
+        assert_eq!(a | b, Bitboard(0b1110));
+        assert_eq!(a ^ b, Bitboard(0b0110));
+        assert_eq!(!Bitboard::EMPTY, Bitboard::FULL);
+        assert_eq!(u64::from(a), 0b1100);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::bitboard::Bitboard;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Square(u8);
+
+impl Square {
+    pub const A1: Self = Self(0);
+    pub const B1: Self = Self(1);
+    pub const C1: Self = Self(2);
+    pub const D1: Self = Self(3);
+    pub const E1: Self = Self(4);
+    pub const F1: Self = Self(5);
+    pub const G1: Self = Self(6);
+    pub const H1: Self = Self(7);
+    pub const A2: Self = Self(8);
+    pub const B2: Self = Self(9);
+    pub const C2: Self = Self(10);
+    pub const D2: Self = Self(11);
+    pub const E2: Self = Self(12);
+    pub const F2: Self = Self(13);
+    pub const G2: Self = Self(14);
+    pub const H2: Self = Self(15);
+    pub const A3: Self = Self(16);
+    pub const B3: Self = Self(17);
+    pub const C3: Self = Self(18);
+    pub const D3: Self = Self(19);
+    pub const E3: Self = Self(20);
+    pub const F3: Self = Self(21);
+    pub const G3: Self = Self(22);
+    pub const H3: Self = Self(23);
+    pub const A4: Self = Self(24);
+    pub const B4: Self = Self(25);
+    pub const C4: Self = Self(26);
+    pub const D4: Self = Self(27);
+    pub const E4: Self = Self(28);
+    pub const F4: Self = Self(29);
+    pub const G4: Self = Self(30);
+    pub const H4: Self = Self(31);
+    pub const A5: Self = Self(32);
+    pub const B5: Self = Self(33);
+    pub const C5: Self = Self(34);
+    pub const D5: Self = Self(35);
+    pub const E5: Self = Self(36);
+    pub const F5: Self = Self(37);
+    pub const G5: Self = Self(38);
+    pub const H5: Self = Self(39);
+    pub const A6: Self = Self(40);
+    pub const B6: Self = Self(41);
+    pub const C6: Self = Self(42);
+    pub const D6: Self = Self(43);
+    pub const E6: Self = Self(44);
+    pub const F6: Self = Self(45);
+    pub const G6: Self = Self(46);
+    pub const H6: Self = Self(47);
+    pub const A7: Self = Self(48);
+    pub const B7: Self = Self(49);
+    pub const C7: Self = Self(50);
+    pub const D7: Self = Self(51);
+    pub const E7: Self = Self(52);
+    pub const F7: Self = Self(53);
+    pub const G7: Self = Self(54);
+    pub const H7: Self = Self(55);
+    pub const A8: Self = Self(56);
+    pub const B8: Self = Self(57);
+    pub const C8: Self = Self(58);
+    pub const D8: Self = Self(59);
+    pub const E8: Self = Self(60);
+    pub const F8: Self = Self(61);
+    pub const G8: Self = Self(62);
+    pub const H8: Self = Self(63);
+
+    pub const fn new(index: usize) -> Self {
+        assert!(index < 64, "square index out of range");
+        Self(index as u8)
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        (index < 64).then_some(Self(index as u8))
+    }
+
+    pub const fn from_file_rank(file: usize, rank: usize) -> Self {
+        assert!(file < 8 && rank < 8, "file or rank out of range");
+        Self((rank * 8 + file) as u8)
+    }
+
+    pub fn from_algebraic(notation: &str) -> Option<Self> {
+        let mut chars = notation.chars();
+        let file = chars.next()?;
+        let rank = chars.next()?;
+
+        if chars.next().is_some() || !('a'..='h').contains(&file) || !('1'..='8').contains(&rank) {
+            return None;
+        }
+
+        Some(Self::from_file_rank(
+            (file as u8 - b'a') as usize,
+            (rank as u8 - b'1') as usize,
+        ))
+    }
+
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub const fn file(self) -> usize {
+        self.0 as usize % 8
+    }
+
+    pub const fn rank(self) -> usize {
+        self.0 as usize / 8
+    }
+
+    pub fn bitboard(self) -> Bitboard {
+        Bitboard::from_square(self)
+    }
+
+    pub fn all() -> impl Iterator<Item = Square> {
+        (0..64).map(Self)
+    }
+}
+
+impl From<Square> for usize {
+    fn from(square: Square) -> Self {
+        square.index()
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            (b'a' + self.file() as u8) as char,
+            (b'1' + self.rank() as u8) as char
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSquareError(pub String);
+
+impl fmt::Display for ParseSquareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid square \"{}\"", self.0)
+    }
+}
+
+impl std::error::Error for ParseSquareError {}
+
+impl FromStr for Square {
+    type Err = ParseSquareError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_algebraic(s).ok_or_else(|| ParseSquareError(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_and_rank() {
+        assert_eq!(Square::E4.file(), 4);
+        assert_eq!(Square::E4.rank(), 3);
+        assert_eq!(Square::from_file_rank(4, 3), Square::E4);
+        assert_eq!(Square::new(28), Square::E4);
+        assert_eq!(usize::from(Square::H8), 63);
+    }
+
+    #[test]
+    fn test_from_index_rejects_out_of_range() {
+        assert_eq!(Square::from_index(0), Some(Square::A1));
+        assert_eq!(Square::from_index(64), None);
+    }
+
+    #[test]
+    fn test_algebraic_round_trip() {
+        for square in Square::all() {
+            assert_eq!(square.to_string().parse::<Square>(), Ok(square));
+        }
+
+        assert_eq!("a1".parse(), Ok(Square::A1));
+        assert_eq!("h8".parse(), Ok(Square::H8));
+    }
+
+    #[test]
+    fn test_invalid_algebraic() {
+        for notation in ["", "e", "i1", "a9", "a0", "e44", "E4"] {
+            assert_eq!(
+                notation.parse::<Square>(),
+                Err(ParseSquareError(notation.to_string()))
+            );
+        }
+    }
+}
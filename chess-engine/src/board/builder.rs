@@ -0,0 +1,69 @@
+use super::{Board, CastlingFiles, CastlingRights, PositionError};
+use crate::bitboard::Square;
+use crate::pieces::piece_type::{Color, Piece};
+use crate::zobrist;
+
+#[derive(Debug, Clone)]
+pub struct BoardBuilder {
+    board: Board,
+}
+
+impl BoardBuilder {
+    pub fn new() -> Self {
+        Self {
+            board: Board::empty(),
+        }
+    }
+
+    pub fn piece(mut self, square: Square, piece: Piece) -> Self {
+        self.board.remove_piece(square);
+        self.board.put_piece(square, piece);
+        self
+    }
+
+    pub fn side_to_move(mut self, color: Color) -> Self {
+        self.board.side_to_move = color;
+        self
+    }
+
+    pub fn castling_rights(mut self, rights: CastlingRights) -> Self {
+        self.board.castling_rights = rights;
+        self
+    }
+
+    // Setting the files switches the board to Chess960 rules, where castling
+    // is played as the king moving onto its rook.
+    pub fn castling_files(mut self, files: CastlingFiles) -> Self {
+        self.board.castling_files = files;
+        self.board.chess960 = true;
+        self
+    }
+
+    pub fn en_passant_square(mut self, square: Option<Square>) -> Self {
+        self.board.en_passant_square = square;
+        self
+    }
+
+    pub fn halfmove_clock(mut self, halfmove_clock: u32) -> Self {
+        self.board.halfmove_clock = halfmove_clock;
+        self
+    }
+
+    pub fn fullmove_number(mut self, fullmove_number: u32) -> Self {
+        self.board.fullmove_number = fullmove_number;
+        self
+    }
+
+    pub fn build(self) -> Result<Board, Vec<PositionError>> {
+        let mut board = self.board;
+        board.hash = zobrist::compute_hash(&board);
+        board.validate()?;
+        Ok(board)
+    }
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
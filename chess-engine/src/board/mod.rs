@@ -0,0 +1,2055 @@
+use crate::bitboard::constants::RANK_1;
+use crate::bitboard::{Bitboard, Square};
+use crate::game_state::check;
+use crate::movement::moves::{Move, MoveFlags, MoveList};
+use crate::movement::validator;
+use crate::pieces::bishop;
+use crate::pieces::king;
+use crate::pieces::knight;
+use crate::pieces::pawn;
+use crate::pieces::piece_type::{Color, MoveError, Piece, PieceType};
+use crate::pieces::queen;
+use crate::pieces::rook;
+use crate::zobrist;
+use std::fmt;
+
+mod builder;
+mod validation;
+
+pub use builder::BoardBuilder;
+pub use validation::PositionError;
+
+const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::King,
+    PieceType::Queen,
+];
+
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CastlingRights {
+    pub white_kingside: bool,
+    pub white_queenside: bool,
+    pub black_kingside: bool,
+    pub black_queenside: bool,
+}
+
+impl CastlingRights {
+    pub const ALL: Self = Self {
+        white_kingside: true,
+        white_queenside: true,
+        black_kingside: true,
+        black_queenside: true,
+    };
+
+    pub const NONE: Self = Self {
+        white_kingside: false,
+        white_queenside: false,
+        black_kingside: false,
+        black_queenside: false,
+    };
+}
+
+// Start files of the king and castling rooks, shared by both colours. Only
+// Chess960 positions differ from the standard e-, h- and a-files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CastlingFiles {
+    pub king: usize,
+    pub kingside_rook: usize,
+    pub queenside_rook: usize,
+}
+
+impl CastlingFiles {
+    pub const STANDARD: Self = Self {
+        king: 4,
+        kingside_rook: 7,
+        queenside_rook: 0,
+    };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenErrorKind {
+    WrongFieldCount(usize),
+    WrongRankCount(usize),
+    InvalidPiece { rank: usize, symbol: char },
+    RankTooLong { rank: usize },
+    RankTooShort { rank: usize },
+    InvalidSideToMove(String),
+    InvalidCastlingRights(String),
+    InvalidEnPassantSquare(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    InvalidPosition(Vec<PositionError>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FenError {
+    pub fen: String,
+    pub kind: FenErrorKind,
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid FEN \"{}\": ", self.fen)?;
+        match &self.kind {
+            FenErrorKind::WrongFieldCount(count) => {
+                write!(f, "expected 4 or 6 fields, found {}", count)
+            }
+            FenErrorKind::WrongRankCount(count) => {
+                write!(f, "expected 8 ranks, found {}", count)
+            }
+            FenErrorKind::InvalidPiece { rank, symbol } => {
+                write!(f, "invalid piece '{}' on rank {}", symbol, rank)
+            }
+            FenErrorKind::RankTooLong { rank } => {
+                write!(f, "rank {} describes more than 8 squares", rank)
+            }
+            FenErrorKind::RankTooShort { rank } => {
+                write!(f, "rank {} describes fewer than 8 squares", rank)
+            }
+            FenErrorKind::InvalidSideToMove(field) => {
+                write!(f, "invalid side to move '{}'", field)
+            }
+            FenErrorKind::InvalidCastlingRights(field) => {
+                write!(f, "invalid castling rights '{}'", field)
+            }
+            FenErrorKind::InvalidEnPassantSquare(field) => {
+                write!(f, "invalid en passant square '{}'", field)
+            }
+            FenErrorKind::InvalidHalfmoveClock(field) => {
+                write!(f, "invalid halfmove clock '{}'", field)
+            }
+            FenErrorKind::InvalidFullmoveNumber(field) => {
+                write!(f, "invalid fullmove number '{}'", field)
+            }
+            FenErrorKind::InvalidPosition(errors) => {
+                write!(f, "illegal position: ")?;
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+#[derive(Debug, Clone, PartialEq)]
+struct UndoInfo {
+    mv: Move,
+    moved: PieceType,
+    captured: Option<PieceType>,
+    castling_rights: CastlingRights,
+    en_passant_square: Option<Square>,
+    halfmove_clock: u32,
+    hash: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Board {
+    pieces: [[Bitboard; 6]; 2],
+    mailbox: [Option<Piece>; 64],
+    pub side_to_move: Color,
+    pub castling_rights: CastlingRights,
+    pub castling_files: CastlingFiles,
+    // Chess960 castling is encoded as the king moving onto its own rook.
+    pub chess960: bool,
+    pub en_passant_square: Option<Square>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+    hash: u64,
+    history: Vec<UndoInfo>,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::from_fen(STARTING_FEN).expect("starting position FEN is valid")
+    }
+}
+
+impl Board {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn empty() -> Self {
+        let mut board = Self {
+            pieces: [[Bitboard::EMPTY; 6]; 2],
+            mailbox: [None; 64],
+            side_to_move: Color::White,
+            castling_rights: CastlingRights::NONE,
+            castling_files: CastlingFiles::STANDARD,
+            chess960: false,
+            en_passant_square: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+            history: Vec::new(),
+        };
+        board.hash = zobrist::compute_hash(&board);
+        board
+    }
+
+    pub fn builder() -> BoardBuilder {
+        BoardBuilder::new()
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let board = Self::from_fen_unchecked(fen)?;
+        board.validate().map_err(|errors| FenError {
+            fen: fen.to_string(),
+            kind: FenErrorKind::InvalidPosition(errors),
+        })?;
+
+        Ok(board)
+    }
+
+    pub(crate) fn from_fen_unchecked(fen: &str) -> Result<Self, FenError> {
+        let error = |kind| FenError {
+            fen: fen.to_string(),
+            kind,
+        };
+
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 4 && fields.len() != 6 {
+            return Err(error(FenErrorKind::WrongFieldCount(fields.len())));
+        }
+
+        let mut board = Self::empty();
+
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(error(FenErrorKind::WrongRankCount(ranks.len())));
+        }
+
+        for (i, rank_str) in ranks.iter().enumerate() {
+            let rank = 7 - i;
+            let mut file = 0;
+
+            for symbol in rank_str.chars() {
+                if let Some(skip) = symbol.to_digit(10).filter(|d| (1..=8).contains(d)) {
+                    file += skip as usize;
+                } else {
+                    let piece = Piece::from_fen_symbol(symbol).ok_or(error(
+                        FenErrorKind::InvalidPiece {
+                            rank: rank + 1,
+                            symbol,
+                        },
+                    ))?;
+                    if file < 8 {
+                        board.put_piece(Square::from_file_rank(file, rank), piece);
+                    }
+                    file += 1;
+                }
+
+                if file > 8 {
+                    return Err(error(FenErrorKind::RankTooLong { rank: rank + 1 }));
+                }
+            }
+
+            if file < 8 {
+                return Err(error(FenErrorKind::RankTooShort { rank: rank + 1 }));
+            }
+        }
+
+        board.side_to_move = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            other => {
+                return Err(error(FenErrorKind::InvalidSideToMove(other.to_string())));
+            }
+        };
+
+        board
+            .parse_castling_field(fields[2])
+            .ok_or(error(FenErrorKind::InvalidCastlingRights(
+                fields[2].to_string(),
+            )))?;
+
+        board.en_passant_square = match fields[3] {
+            "-" => None,
+            square => {
+                let square = Square::from_algebraic(square)
+                    .filter(|square| square.rank() == 2 || square.rank() == 5)
+                    .ok_or(error(FenErrorKind::InvalidEnPassantSquare(
+                        square.to_string(),
+                    )))?;
+                Some(square)
+            }
+        };
+
+        if fields.len() == 6 {
+            board.halfmove_clock = fields[4]
+                .parse()
+                .map_err(|_| error(FenErrorKind::InvalidHalfmoveClock(fields[4].to_string())))?;
+            board.fullmove_number =
+                fields[5]
+                    .parse()
+                    .ok()
+                    .filter(|&number| number > 0)
+                    .ok_or(error(FenErrorKind::InvalidFullmoveNumber(
+                        fields[5].to_string(),
+                    )))?;
+        }
+
+        board.hash = zobrist::compute_hash(&board);
+
+        Ok(board)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+
+        for rank in (0..8).rev() {
+            let mut empty_squares = 0;
+            for file in 0..8 {
+                match self.piece_at(Square::from_file_rank(file, rank)) {
+                    Some(piece) => {
+                        if empty_squares > 0 {
+                            fen.push_str(&empty_squares.to_string());
+                            empty_squares = 0;
+                        }
+                        fen.push(piece.fen_symbol());
+                    }
+                    None => empty_squares += 1,
+                }
+            }
+            if empty_squares > 0 {
+                fen.push_str(&empty_squares.to_string());
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+
+        fen.push_str(match self.side_to_move {
+            Color::White => " w ",
+            Color::Black => " b ",
+        });
+
+        fen.push_str(&self.castling_field(false));
+
+        fen.push(' ');
+        match self.en_passant_square {
+            Some(square) => fen.push_str(&square.to_string()),
+            None => fen.push('-'),
+        }
+
+        fen.push_str(&format!(
+            " {} {}",
+            self.halfmove_clock, self.fullmove_number
+        ));
+
+        fen
+    }
+
+    // Like `to_fen`, but Chess960 castling rights are always written as rook
+    // files ("HAha") instead of X-FEN's "KQkq" where that is unambiguous.
+    pub fn to_shredder_fen(&self) -> String {
+        let fen = self.to_fen();
+        let mut fields: Vec<&str> = fen.split(' ').collect();
+        let castling = self.castling_field(true);
+        fields[2] = &castling;
+        fields.join(" ")
+    }
+
+    pub fn zobrist_key(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn print(&self) {
+        println!("  +---+---+---+---+---+---+---+---+");
+        for rank in (0..8).rev() {
+            print!("{} |", rank + 1);
+            for file in 0..8 {
+                let is_dark_square = (rank + file) % 2 == 1;
+
+                match self.piece_at(Square::from_file_rank(file, rank)) {
+                    Some(piece) => print!(" {} ", piece_to_unicode(piece)),
+                    None if is_dark_square => print!("░░░"),
+                    None => print!("   "),
+                }
+                print!("|");
+            }
+            println!();
+            println!("  +---+---+---+---+---+---+---+---+");
+        }
+        println!("    a   b   c   d   e   f   g   h  ");
+        println!();
+        println!("  White: ♙ ♘ ♗ ♖ ♔ ♕   Black: ♟ ♞ ♝ ♜ ♚ ♛");
+    }
+
+    pub fn white_pieces(&self) -> Bitboard {
+        self.occupancy(Color::White)
+    }
+
+    pub fn black_pieces(&self) -> Bitboard {
+        self.occupancy(Color::Black)
+    }
+
+    pub fn all_pieces(&self) -> Bitboard {
+        self.white_pieces() | self.black_pieces()
+    }
+
+    pub fn occupancy(&self, color: Color) -> Bitboard {
+        self.pieces[color as usize]
+            .iter()
+            .fold(Bitboard::EMPTY, |occupancy, &pieces| occupancy | pieces)
+    }
+
+    pub fn pieces(&self, piece_type: PieceType, color: Color) -> Bitboard {
+        self.pieces[color as usize][piece_type as usize]
+    }
+
+    pub fn toggle_side_to_move(&mut self) {
+        self.side_to_move = self.side_to_move.opposite();
+    }
+
+    pub fn piece_at(&self, square: Square) -> Option<Piece> {
+        self.mailbox[square.index()]
+    }
+
+    pub fn get_legal_moves(&self, from: Square) -> Bitboard {
+        let candidates = self.get_pseudo_legal_moves(from);
+
+        if let Some(Piece {
+            piece_type: PieceType::King,
+            color,
+        }) = self.piece_at(from)
+        {
+            let occupancy = self.all_pieces() & !from.bitboard();
+            let castling = Bitboard(self.get_castling_moves(from.index()));
+
+            // The castling path is already known to be safe, but in Chess960 the
+            // rook may have been shielding the king's destination.
+            return (candidates & !castling)
+                .iter()
+                .filter(|&to| {
+                    !check::is_square_attacked_with_occupancy(self, to, color.opposite(), occupancy)
+                })
+                .chain(
+                    castling
+                        .iter()
+                        .filter(|&to| !self.chess960 || !self.leaves_king_in_check(from, to)),
+                )
+                .collect();
+        }
+
+        candidates
+            .iter()
+            .filter(|&to| !self.leaves_king_in_check(from, to))
+            .collect()
+    }
+
+    pub fn generate_moves(&self) -> MoveList {
+        let mut moves = MoveList::new();
+        let (own_pieces, opponent_pieces) = match self.side_to_move {
+            Color::White => (self.white_pieces(), self.black_pieces()),
+            Color::Black => (self.black_pieces(), self.white_pieces()),
+        };
+
+        for from in own_pieces {
+            let piece_type = match self.piece_at(from) {
+                Some(piece) => piece.piece_type,
+                None => continue,
+            };
+
+            for to in self.get_legal_moves(from) {
+                let mut flags = MoveFlags::QUIET;
+                if opponent_pieces.contains(to) {
+                    flags |= MoveFlags::CAPTURE;
+                }
+
+                match piece_type {
+                    PieceType::Pawn if Some(to) == self.en_passant_square => {
+                        flags |= MoveFlags::CAPTURE | MoveFlags::EN_PASSANT;
+                    }
+                    PieceType::Pawn if from.index().abs_diff(to.index()) == 16 => {
+                        flags |= MoveFlags::DOUBLE_PUSH;
+                    }
+                    PieceType::King if self.is_castling(from, to) => {
+                        flags |= MoveFlags::CASTLING;
+                    }
+                    _ => {}
+                }
+
+                if piece_type == PieceType::Pawn && (to.rank() == 0 || to.rank() == 7) {
+                    for promotion in [
+                        PieceType::Queen,
+                        PieceType::Rook,
+                        PieceType::Bishop,
+                        PieceType::Knight,
+                    ] {
+                        moves.push(Move::new(from, to, Some(promotion), flags));
+                    }
+                } else {
+                    moves.push(Move::new(from, to, None, flags));
+                }
+            }
+        }
+
+        moves
+    }
+
+    pub fn get_pseudo_legal_moves(&self, from: Square) -> Bitboard {
+        let piece = match self.piece_at(from) {
+            Some(piece) if piece.color == self.side_to_move => piece,
+            _ => return Bitboard::EMPTY,
+        };
+
+        let from = from.index();
+        let white = self.pieces(piece.piece_type, Color::White).0;
+        let black = self.pieces(piece.piece_type, Color::Black).0;
+        let white_pieces = self.white_pieces().0;
+        let black_pieces = self.black_pieces().0;
+        let side = self.side_to_move;
+
+        Bitboard(match piece.piece_type {
+            PieceType::Pawn => pawn::get_pawn_moves(
+                from,
+                white,
+                black,
+                white_pieces,
+                black_pieces,
+                self.en_passant_square.map(Square::index),
+                side,
+            ),
+            PieceType::Knight => {
+                knight::get_knight_moves(from, white, black, white_pieces, black_pieces, side)
+            }
+            PieceType::Bishop => {
+                bishop::get_bishop_moves(from, white, black, white_pieces, black_pieces, side)
+            }
+            PieceType::Rook => {
+                rook::get_rook_moves(from, white, black, white_pieces, black_pieces, side)
+            }
+            PieceType::King => {
+                king::get_king_moves(from, white, black, white_pieces, black_pieces, side)
+                    | self.get_castling_moves(from)
+            }
+            PieceType::Queen => {
+                queen::get_queen_moves(from, white, black, white_pieces, black_pieces, side)
+            }
+        })
+    }
+
+    pub fn make_move(&mut self, from: Square, to: Square) -> Result<(), MoveError> {
+        self.make_move_checked(from, to, None)
+    }
+
+    pub fn make_move_with_promotion(
+        &mut self,
+        from: Square,
+        to: Square,
+        promotion: PieceType,
+    ) -> Result<(), MoveError> {
+        self.make_move_checked(from, to, Some(promotion))
+    }
+
+    pub fn play_move(&mut self, mv: Move) -> Result<(), MoveError> {
+        self.make_move_checked(mv.from(), mv.to(), mv.promotion())
+    }
+
+    pub(crate) fn make_move_unchecked(&mut self, mv: Move) {
+        if let Some(undo) = self.apply_move(mv.from(), mv.to(), mv.promotion()) {
+            self.history.push(undo);
+        }
+        self.debug_assert_consistent();
+    }
+
+    fn make_move_checked(
+        &mut self,
+        from: Square,
+        to: Square,
+        promotion: Option<PieceType>,
+    ) -> Result<(), MoveError> {
+        let pseudo_legal_moves = self.get_pseudo_legal_moves(from);
+
+        validator::validate_move(
+            from,
+            to,
+            self.side_to_move,
+            self.white_pieces(),
+            self.black_pieces(),
+            pseudo_legal_moves,
+        )?;
+
+        let piece = self.piece_at(from).ok_or(MoveError::NoPieceAtSource)?;
+
+        validator::validate_promotion(piece.piece_type, to, promotion)?;
+
+        if self.leaves_king_in_check(from, to) {
+            return Err(MoveError::LeavesKingInCheck);
+        }
+
+        if let Some(undo) = self.apply_move(from, to, promotion) {
+            self.history.push(undo);
+        }
+        self.debug_assert_consistent();
+
+        Ok(())
+    }
+
+    pub fn unmake_move(&mut self) -> Option<Move> {
+        let undo = self.history.pop()?;
+        let mv = undo.mv;
+
+        self.toggle_side_to_move();
+        let us = self.side_to_move;
+
+        if mv.is_castling() {
+            let (king_to, rook_from, rook_to) = self.castling_squares(mv.from(), mv.to());
+            self.remove_piece(king_to);
+            self.remove_piece(rook_to);
+            self.put_piece(mv.from(), Piece::new(PieceType::King, us));
+            self.put_piece(rook_from, Piece::new(PieceType::Rook, us));
+        } else {
+            self.remove_piece(mv.to());
+            self.put_piece(mv.from(), Piece::new(undo.moved, us));
+        }
+
+        if let Some(captured) = undo.captured {
+            let captured_square = if mv.is_en_passant() {
+                en_passant_capture_square(mv.to(), us)
+            } else {
+                mv.to()
+            };
+            self.put_piece(captured_square, Piece::new(captured, us.opposite()));
+        }
+
+        self.castling_rights = undo.castling_rights;
+        self.en_passant_square = undo.en_passant_square;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.hash = undo.hash;
+        if us == Color::Black {
+            self.fullmove_number -= 1;
+        }
+        self.debug_assert_consistent();
+
+        Some(mv)
+    }
+
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn repetition_count(&self) -> usize {
+        let reversible_plies = (self.halfmove_clock as usize).min(self.history.len());
+
+        1 + self
+            .history
+            .iter()
+            .rev()
+            .take(reversible_plies)
+            .skip(1)
+            .step_by(2)
+            .filter(|undo| undo.hash == self.hash)
+            .count()
+    }
+
+    fn apply_move(
+        &mut self,
+        from: Square,
+        to: Square,
+        promotion: Option<PieceType>,
+    ) -> Option<UndoInfo> {
+        let piece_type = self.piece_at(from)?.piece_type;
+        let us = self.side_to_move;
+        let distance = from.index().abs_diff(to.index());
+
+        let is_en_passant = piece_type == PieceType::Pawn && Some(to) == self.en_passant_square;
+        let is_castling = piece_type == PieceType::King && self.is_castling(from, to);
+        let captured_square = if is_en_passant {
+            en_passant_capture_square(to, us)
+        } else {
+            to
+        };
+        let captured = self
+            .piece_at(captured_square)
+            .filter(|_| !is_castling)
+            .map(|captured| captured.piece_type);
+        let is_capture = captured.is_some();
+
+        let mut flags = MoveFlags::QUIET;
+        if is_capture {
+            flags |= MoveFlags::CAPTURE;
+        }
+        if is_en_passant {
+            flags |= MoveFlags::EN_PASSANT;
+        }
+        if is_castling {
+            flags |= MoveFlags::CASTLING;
+        }
+        if piece_type == PieceType::Pawn && distance == 16 {
+            flags |= MoveFlags::DOUBLE_PUSH;
+        }
+
+        let undo = UndoInfo {
+            mv: Move::new(from, to, promotion, flags),
+            moved: piece_type,
+            captured,
+            castling_rights: self.castling_rights,
+            en_passant_square: self.en_passant_square,
+            halfmove_clock: self.halfmove_clock,
+            hash: self.hash,
+        };
+
+        self.hash ^=
+            zobrist::castling_key(self.castling_rights) ^ zobrist::en_passant_component(self);
+
+        if is_castling {
+            let (king_to, rook_from, rook_to) = self.castling_squares(from, to);
+            self.remove_piece(from);
+            self.remove_piece(rook_from);
+            self.put_piece(king_to, Piece::new(PieceType::King, us));
+            self.put_piece(rook_to, Piece::new(PieceType::Rook, us));
+        } else {
+            if is_capture {
+                self.remove_piece(captured_square);
+            }
+            self.remove_piece(from);
+            self.put_piece(to, Piece::new(promotion.unwrap_or(piece_type), us));
+        }
+
+        self.update_castling_rights(from, to);
+
+        if piece_type == PieceType::Pawn || is_capture {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if us == Color::Black {
+            self.fullmove_number += 1;
+        }
+        self.en_passant_square = if piece_type == PieceType::Pawn && distance == 16 {
+            Some(Square::new((from.index() + to.index()) / 2))
+        } else {
+            None
+        };
+
+        self.toggle_side_to_move();
+
+        self.hash ^= zobrist::side_to_move_key()
+            ^ zobrist::castling_key(self.castling_rights)
+            ^ zobrist::en_passant_component(self);
+
+        Some(undo)
+    }
+
+    fn put_piece(&mut self, square: Square, piece: Piece) {
+        self.pieces[piece.color as usize][piece.piece_type as usize].insert(square);
+        self.mailbox[square.index()] = Some(piece);
+        self.hash ^= zobrist::piece_key(piece.piece_type, piece.color, square);
+    }
+
+    fn remove_piece(&mut self, square: Square) -> Option<Piece> {
+        let piece = self.mailbox[square.index()].take()?;
+        self.pieces[piece.color as usize][piece.piece_type as usize].remove(square);
+        self.hash ^= zobrist::piece_key(piece.piece_type, piece.color, square);
+        Some(piece)
+    }
+
+    fn debug_assert_consistent(&self) {
+        debug_assert_eq!(
+            self.hash,
+            zobrist::compute_hash(self),
+            "incremental Zobrist key diverged from recomputation"
+        );
+        debug_assert!(
+            self.mailbox_matches_bitboards(),
+            "mailbox and piece bitboards are out of sync"
+        );
+    }
+
+    fn mailbox_matches_bitboards(&self) -> bool {
+        let mut bitboard_count = 0;
+
+        for color in [Color::White, Color::Black] {
+            for piece_type in PIECE_TYPES {
+                let pieces = self.pieces(piece_type, color);
+                bitboard_count += pieces.count();
+
+                if pieces
+                    .iter()
+                    .any(|square| self.piece_at(square) != Some(Piece::new(piece_type, color)))
+                {
+                    return false;
+                }
+            }
+        }
+
+        bitboard_count as usize == self.mailbox.iter().flatten().count()
+    }
+
+    fn scratch_copy(&self) -> Board {
+        Board {
+            history: Vec::new(),
+            ..*self
+        }
+    }
+
+    fn leaves_king_in_check(&self, from: Square, to: Square) -> bool {
+        let mut board = self.scratch_copy();
+        board.apply_move(from, to, None);
+
+        check::is_in_check(&board, self.side_to_move)
+    }
+
+    fn update_castling_rights(&mut self, from: Square, to: Square) {
+        let files = self.castling_files;
+        let rights = &mut self.castling_rights;
+
+        for square in [from, to] {
+            let (kingside, queenside) = match square.rank() {
+                0 => (&mut rights.white_kingside, &mut rights.white_queenside),
+                7 => (&mut rights.black_kingside, &mut rights.black_queenside),
+                _ => continue,
+            };
+
+            if square.file() == files.king {
+                *kingside = false;
+                *queenside = false;
+            } else if square.file() == files.kingside_rook {
+                *kingside = false;
+            } else if square.file() == files.queenside_rook {
+                *queenside = false;
+            }
+        }
+    }
+
+    fn castling_rook(&self, color: Color, kingside: bool) -> Option<Square> {
+        let rights = self.castling_rights;
+        let (allowed, file) = match (color, kingside) {
+            (Color::White, true) => (rights.white_kingside, self.castling_files.kingside_rook),
+            (Color::White, false) => (rights.white_queenside, self.castling_files.queenside_rook),
+            (Color::Black, true) => (rights.black_kingside, self.castling_files.kingside_rook),
+            (Color::Black, false) => (rights.black_queenside, self.castling_files.queenside_rook),
+        };
+        let back_rank = match color {
+            Color::White => 0,
+            Color::Black => 7,
+        };
+
+        allowed.then(|| Square::from_file_rank(file, back_rank))
+    }
+
+    fn get_castling_moves(&self, from: usize) -> u64 {
+        let us = self.side_to_move;
+        let back_rank = match us {
+            Color::White => 0,
+            Color::Black => 7,
+        };
+        if from != Square::from_file_rank(self.castling_files.king, back_rank).index() {
+            return 0;
+        }
+
+        let kingside_rook = self.castling_rook(us, true);
+        let queenside_rook = self.castling_rook(us, false);
+        let own_rooks = self.pieces(PieceType::Rook, us).0;
+
+        let king_destinations = king::get_castling_moves(
+            from,
+            kingside_rook.map(Square::index),
+            queenside_rook.map(Square::index),
+            own_rooks,
+            self.all_pieces().0,
+            |square| check::is_square_attacked(self, Square::new(square), us.opposite()),
+        );
+
+        if !self.chess960 {
+            return king_destinations;
+        }
+
+        let mut moves = 0u64;
+        for (rook, king_file) in [(kingside_rook, 6), (queenside_rook, 2)] {
+            if let Some(rook) = rook {
+                if king_destinations & Square::from_file_rank(king_file, back_rank).bitboard().0
+                    != 0
+                {
+                    moves |= rook.bitboard().0;
+                }
+            }
+        }
+        moves
+    }
+
+    fn is_castling(&self, from: Square, to: Square) -> bool {
+        if self.chess960 {
+            self.piece_at(to) == Some(Piece::new(PieceType::Rook, self.side_to_move))
+                && from.rank() == to.rank()
+        } else {
+            from.index().abs_diff(to.index()) == 2
+        }
+    }
+
+    // Returns the king's destination and the rook's origin and destination.
+    fn castling_squares(&self, king_from: Square, to: Square) -> (Square, Square, Square) {
+        let back_rank = king_from.rank();
+        let kingside = to.file() > king_from.file();
+        let (rook_file, king_file, rook_to_file) = if kingside {
+            (self.castling_files.kingside_rook, 6, 5)
+        } else {
+            (self.castling_files.queenside_rook, 2, 3)
+        };
+
+        (
+            Square::from_file_rank(king_file, back_rank),
+            Square::from_file_rank(rook_file, back_rank),
+            Square::from_file_rank(rook_to_file, back_rank),
+        )
+    }
+
+    // Accepts "KQkq", X-FEN (which adds rook files where "K"/"Q" would be
+    // ambiguous) and Shredder-FEN ("HAha").
+    fn parse_castling_field(&mut self, field: &str) -> Option<()> {
+        self.castling_rights = CastlingRights::NONE;
+        self.castling_files = CastlingFiles::STANDARD;
+        self.chess960 = false;
+        if field == "-" {
+            return Some(());
+        }
+
+        let mut king_file = None;
+        let mut kingside_rook = None;
+        let mut queenside_rook = None;
+        let mut explicit_files = false;
+
+        for symbol in field.chars() {
+            let color = if symbol.is_ascii_uppercase() {
+                Color::White
+            } else {
+                Color::Black
+            };
+            let back_rank = match color {
+                Color::White => 0,
+                Color::Black => 7,
+            };
+            let own_king = (self.pieces(PieceType::King, color)
+                & Bitboard(RANK_1 << (back_rank * 8)))
+            .lsb()
+            .map(|square| square.file());
+            let own_rook_files: Vec<usize> = (self.pieces(PieceType::Rook, color)
+                & Bitboard(RANK_1 << (back_rank * 8)))
+            .iter()
+            .map(|square| square.file())
+            .collect();
+
+            let (kingside, rook_file) = match symbol.to_ascii_lowercase() {
+                'k' => {
+                    let outermost = own_king.and_then(|king| {
+                        own_rook_files
+                            .iter()
+                            .copied()
+                            .filter(|&file| file > king)
+                            .max()
+                    });
+                    (
+                        true,
+                        outermost.unwrap_or(CastlingFiles::STANDARD.kingside_rook),
+                    )
+                }
+                'q' => {
+                    let outermost = own_king.and_then(|king| {
+                        own_rook_files
+                            .iter()
+                            .copied()
+                            .filter(|&file| file < king)
+                            .min()
+                    });
+                    (
+                        false,
+                        outermost.unwrap_or(CastlingFiles::STANDARD.queenside_rook),
+                    )
+                }
+                file @ 'a'..='h' => {
+                    let file = file as usize - 'a' as usize;
+                    explicit_files = true;
+                    match own_king {
+                        Some(king) if file != king => (file > king, file),
+                        _ => return None,
+                    }
+                }
+                _ => return None,
+            };
+
+            let right = match (color, kingside) {
+                (Color::White, true) => &mut self.castling_rights.white_kingside,
+                (Color::White, false) => &mut self.castling_rights.white_queenside,
+                (Color::Black, true) => &mut self.castling_rights.black_kingside,
+                (Color::Black, false) => &mut self.castling_rights.black_queenside,
+            };
+            if *right {
+                return None;
+            }
+            *right = true;
+
+            king_file = king_file.or(own_king);
+            let rook = if kingside {
+                &mut kingside_rook
+            } else {
+                &mut queenside_rook
+            };
+            *rook = rook.or(Some(rook_file));
+        }
+
+        self.castling_files = CastlingFiles {
+            king: king_file.unwrap_or(CastlingFiles::STANDARD.king),
+            kingside_rook: kingside_rook.unwrap_or(CastlingFiles::STANDARD.kingside_rook),
+            queenside_rook: queenside_rook.unwrap_or(CastlingFiles::STANDARD.queenside_rook),
+        };
+        self.chess960 = explicit_files || self.castling_files != CastlingFiles::STANDARD;
+
+        Some(())
+    }
+
+    fn castling_field(&self, shredder: bool) -> String {
+        let mut field = String::new();
+
+        for (color, kingside, symbol) in [
+            (Color::White, true, 'K'),
+            (Color::White, false, 'Q'),
+            (Color::Black, true, 'k'),
+            (Color::Black, false, 'q'),
+        ] {
+            let rook = match self.castling_rook(color, kingside) {
+                Some(rook) => rook,
+                None => continue,
+            };
+
+            // X-FEN keeps "K"/"Q" unless another rook sits further out.
+            let outer_squares = if kingside {
+                rook.file() + 1..8
+            } else {
+                0..rook.file()
+            };
+            let outermost = !outer_squares.into_iter().any(|file| {
+                self.piece_at(Square::from_file_rank(file, rook.rank()))
+                    == Some(Piece::new(PieceType::Rook, color))
+            });
+
+            if !self.chess960 || (!shredder && outermost) {
+                field.push(symbol);
+            } else {
+                let file = (b'a' + rook.file() as u8) as char;
+                field.push(match color {
+                    Color::White => file.to_ascii_uppercase(),
+                    Color::Black => file,
+                });
+            }
+        }
+
+        if field.is_empty() {
+            field.push('-');
+        }
+        field
+    }
+}
+
+fn en_passant_capture_square(to: Square, capturing_side: Color) -> Square {
+    match capturing_side {
+        Color::White => Square::new(to.index() - 8),
+        Color::Black => Square::new(to.index() + 8),
+    }
+}
+
+fn piece_to_unicode(piece: Piece) -> char {
+    match (piece.color, piece.piece_type) {
+        (Color::White, PieceType::Pawn) => '♙',
+        (Color::Black, PieceType::Pawn) => '♟',
+        (Color::White, PieceType::Knight) => '♘',
+        (Color::Black, PieceType::Knight) => '♞',
+        (Color::White, PieceType::Bishop) => '♗',
+        (Color::Black, PieceType::Bishop) => '♝',
+        (Color::White, PieceType::Rook) => '♖',
+        (Color::Black, PieceType::Rook) => '♜',
+        (Color::White, PieceType::King) => '♔',
+        (Color::Black, PieceType::King) => '♚',
+        (Color::White, PieceType::Queen) => '♕',
+        (Color::Black, PieceType::Queen) => '♛',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::constants;
+
+    #[test]
+    fn test_initial_board_state() {
+        let board = Board::new();
+        assert_eq!(
+            board.pieces(PieceType::Pawn, Color::White),
+            Bitboard(constants::RANK_2)
+        );
+        assert_eq!(
+            board.pieces(PieceType::Pawn, Color::Black),
+            Bitboard(constants::RANK_7)
+        );
+        assert_eq!(
+            board.pieces(PieceType::Knight, Color::White),
+            Bitboard(0x0000_0000_0000_0042)
+        );
+        assert_eq!(
+            board.pieces(PieceType::Knight, Color::Black),
+            Bitboard(0x4200_0000_0000_0000)
+        );
+        assert_eq!(
+            board.pieces(PieceType::Bishop, Color::White),
+            Bitboard(0x0000_0000_0000_0024)
+        );
+        assert_eq!(
+            board.pieces(PieceType::Bishop, Color::Black),
+            Bitboard(0x2400_0000_0000_0000)
+        );
+        assert_eq!(
+            board.pieces(PieceType::Rook, Color::White),
+            Bitboard(0x0000_0000_0000_0081)
+        );
+        assert_eq!(
+            board.pieces(PieceType::Rook, Color::Black),
+            Bitboard(0x8100_0000_0000_0000)
+        );
+        assert_eq!(
+            board.pieces(PieceType::King, Color::White),
+            Bitboard(0x0000_0000_0000_0010)
+        );
+        assert_eq!(
+            board.pieces(PieceType::King, Color::Black),
+            Bitboard(0x1000_0000_0000_0000)
+        );
+        assert_eq!(
+            board.pieces(PieceType::Queen, Color::White),
+            Bitboard(0x0000_0000_0000_0008)
+        );
+        assert_eq!(
+            board.pieces(PieceType::Queen, Color::Black),
+            Bitboard(0x0800_0000_0000_0000)
+        );
+        assert_eq!(board.side_to_move, Color::White);
+    }
+
+    #[test]
+    fn test_knight_move() {
+        let mut board = Board::new();
+
+        assert!(board.make_move(Square::B1, Square::A3).is_ok());
+        assert!(board
+            .pieces(PieceType::Knight, Color::White)
+            .contains(Square::A3));
+        assert!(!board
+            .pieces(PieceType::Knight, Color::White)
+            .contains(Square::B1));
+        assert_eq!(board.side_to_move, Color::Black);
+    }
+
+    #[test]
+    fn test_knight_capture() {
+        let mut board =
+            Board::from_fen("rnbqkbnr/8/8/8/8/p7/PPPPPPPP/RNBQKB1R w KQkq - 0 1").unwrap();
+
+        assert!(board.make_move(Square::B1, Square::A3).is_ok());
+        assert!(board
+            .pieces(PieceType::Knight, Color::White)
+            .contains(Square::A3));
+        assert!(!board
+            .pieces(PieceType::Pawn, Color::Black)
+            .contains(Square::A3));
+    }
+
+    #[test]
+    fn test_bishop_move() {
+        let mut board = Board::from_fen("rnbqkbnr/8/8/8/3B4/8/8/RN1QK1NR w KQkq - 0 1").unwrap();
+
+        assert!(board.make_move(Square::D4, Square::E5).is_ok());
+        assert!(board
+            .pieces(PieceType::Bishop, Color::White)
+            .contains(Square::E5));
+        assert!(!board
+            .pieces(PieceType::Bishop, Color::White)
+            .contains(Square::D4));
+        assert_eq!(board.side_to_move, Color::Black);
+    }
+
+    #[test]
+    fn test_bishop_capture() {
+        let mut board = Board::from_fen("rnbqkbnr/8/8/8/5p2/8/8/RNBQK1NR w KQkq - 0 1").unwrap();
+
+        assert!(board.make_move(Square::C1, Square::F4).is_ok());
+        assert!(board
+            .pieces(PieceType::Bishop, Color::White)
+            .contains(Square::F4));
+        assert!(!board
+            .pieces(PieceType::Pawn, Color::Black)
+            .contains(Square::F4));
+    }
+
+    #[test]
+    fn test_rook_move() {
+        let mut board = Board::from_fen("rnbqkbnr/8/8/8/3R4/8/8/1NBQKBN1 w kq - 0 1").unwrap();
+
+        assert!(board.make_move(Square::D4, Square::H4).is_ok());
+        assert!(board
+            .pieces(PieceType::Rook, Color::White)
+            .contains(Square::H4));
+        assert!(!board
+            .pieces(PieceType::Rook, Color::White)
+            .contains(Square::D4));
+        assert_eq!(board.side_to_move, Color::Black);
+    }
+
+    #[test]
+    fn test_rook_capture() {
+        let mut board = Board::from_fen("rnbqkbnr/8/8/8/8/8/p7/RNBQKBN1 w Qkq - 0 1").unwrap();
+
+        assert!(board.make_move(Square::A1, Square::A2).is_ok());
+        assert!(board
+            .pieces(PieceType::Rook, Color::White)
+            .contains(Square::A2));
+        assert!(!board
+            .pieces(PieceType::Pawn, Color::Black)
+            .contains(Square::A2));
+    }
+
+    #[test]
+    fn test_get_piece_type() {
+        let board = Board::new();
+
+        assert_eq!(
+            board.piece_at(Square::A2),
+            Some(Piece::new(PieceType::Pawn, Color::White))
+        );
+        assert_eq!(
+            board.piece_at(Square::B1),
+            Some(Piece::new(PieceType::Knight, Color::White))
+        );
+        assert_eq!(
+            board.piece_at(Square::C1),
+            Some(Piece::new(PieceType::Bishop, Color::White))
+        );
+        assert_eq!(
+            board.piece_at(Square::A1),
+            Some(Piece::new(PieceType::Rook, Color::White))
+        );
+        assert_eq!(
+            board.piece_at(Square::A7),
+            Some(Piece::new(PieceType::Pawn, Color::Black))
+        );
+        assert_eq!(
+            board.piece_at(Square::B8),
+            Some(Piece::new(PieceType::Knight, Color::Black))
+        );
+        assert_eq!(
+            board.piece_at(Square::C8),
+            Some(Piece::new(PieceType::Bishop, Color::Black))
+        );
+        assert_eq!(
+            board.piece_at(Square::A8),
+            Some(Piece::new(PieceType::Rook, Color::Black))
+        );
+        assert_eq!(board.piece_at(Square::E3), None);
+    }
+
+    #[test]
+    fn test_king_move() {
+        let mut board =
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1").unwrap();
+
+        board.make_move(Square::E1, Square::E2).unwrap();
+
+        assert_eq!(
+            board.pieces(PieceType::King, Color::White),
+            Square::E2.bitboard()
+        );
+        assert_eq!(board.side_to_move, Color::Black);
+    }
+
+    #[test]
+    fn test_king_capture() {
+        let mut board =
+            Board::from_fen("rnbqkbnr/pppp1ppp/8/8/8/8/PPPPpPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+
+        board.make_move(Square::E1, Square::E2).unwrap();
+
+        assert_eq!(
+            board.pieces(PieceType::King, Color::White),
+            Square::E2.bitboard()
+        );
+        assert!(!board
+            .pieces(PieceType::Pawn, Color::Black)
+            .contains(Square::E2));
+        assert_eq!(board.side_to_move, Color::Black);
+    }
+
+    #[test]
+    fn test_queen_move() {
+        let mut board =
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPP1PPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+
+        board.make_move(Square::D1, Square::D3).unwrap();
+
+        assert_eq!(
+            board.pieces(PieceType::Queen, Color::White),
+            Square::D3.bitboard()
+        );
+        assert_eq!(board.side_to_move, Color::Black);
+    }
+
+    #[test]
+    fn test_queen_capture() {
+        let mut board =
+            Board::from_fen("rnbqkbnr/ppp1pppp/8/8/8/8/PPPpPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+
+        board.make_move(Square::D1, Square::D2).unwrap();
+
+        assert_eq!(
+            board.pieces(PieceType::Queen, Color::White),
+            Square::D2.bitboard()
+        );
+        assert!(!board
+            .pieces(PieceType::Pawn, Color::Black)
+            .contains(Square::D2));
+        assert_eq!(board.side_to_move, Color::Black);
+    }
+
+    #[test]
+    fn test_queen_diagonal_move() {
+        let mut board =
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PP1P1PPP/RNBQKBNR w KQkq - 0 1").unwrap();
+
+        board.make_move(Square::D1, Square::C2).unwrap();
+
+        assert_eq!(
+            board.pieces(PieceType::Queen, Color::White),
+            Square::C2.bitboard()
+        );
+        assert_eq!(board.side_to_move, Color::Black);
+    }
+
+    #[test]
+    fn test_move_counters() {
+        let mut board = Board::new();
+
+        board.make_move(Square::G1, Square::F3).unwrap();
+        assert_eq!((board.halfmove_clock, board.fullmove_number), (1, 1));
+
+        board.make_move(Square::B8, Square::C6).unwrap();
+        assert_eq!((board.halfmove_clock, board.fullmove_number), (2, 2));
+
+        board.make_move(Square::E2, Square::E4).unwrap();
+        assert_eq!((board.halfmove_clock, board.fullmove_number), (0, 2));
+    }
+
+    #[test]
+    fn test_from_fen_starting_position() {
+        let board = Board::from_fen(STARTING_FEN).unwrap();
+
+        assert_eq!(board, Board::new());
+    }
+
+    #[test]
+    fn test_to_fen_starting_position() {
+        assert_eq!(Board::new().to_fen(), STARTING_FEN);
+    }
+
+    #[test]
+    fn test_fen_round_trip() {
+        let fens = [
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 b - - 17 42",
+        ];
+
+        for fen in fens {
+            assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn test_from_fen_fields() {
+        let board =
+            Board::from_fen("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b Kq e3 3 12").unwrap();
+
+        assert_eq!(board.side_to_move, Color::Black);
+        assert_eq!(
+            board.castling_rights,
+            CastlingRights {
+                white_kingside: true,
+                white_queenside: false,
+                black_kingside: false,
+                black_queenside: true,
+            }
+        );
+        assert_eq!(board.en_passant_square, Some(Square::E3));
+        assert_eq!(board.halfmove_clock, 3);
+        assert_eq!(board.fullmove_number, 12);
+        assert_eq!(
+            board.piece_at(Square::D4),
+            Some(Piece::new(PieceType::Pawn, Color::Black))
+        );
+    }
+
+    #[test]
+    fn test_from_fen_without_move_counters() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - -").unwrap();
+
+        assert_eq!(board.halfmove_clock, 0);
+        assert_eq!(board.fullmove_number, 1);
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+    }
+
+    #[test]
+    fn test_from_fen_errors() {
+        let cases = [
+            ("8/8/8/8/8/8/8/8 w - - 0", FenErrorKind::WrongFieldCount(5)),
+            ("8/8/8/8/8/8/8 w - - 0 1", FenErrorKind::WrongRankCount(7)),
+            (
+                "4k3/8/8/8/8/8/8/4X3 w - - 0 1",
+                FenErrorKind::InvalidPiece {
+                    rank: 1,
+                    symbol: 'X',
+                },
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K4 w - - 0 1",
+                FenErrorKind::RankTooLong { rank: 1 },
+            ),
+            (
+                "4k3/8/8/8/7/8/8/4K3 w - - 0 1",
+                FenErrorKind::RankTooShort { rank: 4 },
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 x - - 0 1",
+                FenErrorKind::InvalidSideToMove("x".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w KK - 0 1",
+                FenErrorKind::InvalidCastlingRights("KK".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - e4 0 1",
+                FenErrorKind::InvalidEnPassantSquare("e4".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - - -1 1",
+                FenErrorKind::InvalidHalfmoveClock("-1".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - - 0 0",
+                FenErrorKind::InvalidFullmoveNumber("0".to_string()),
+            ),
+        ];
+
+        for (fen, kind) in cases {
+            assert_eq!(
+                Board::from_fen(fen),
+                Err(FenError {
+                    fen: fen.to_string(),
+                    kind,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_fen_error_message() {
+        let error = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - z9 0 1").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "invalid FEN \"4k3/8/8/8/8/8/8/4K3 w - z9 0 1\": invalid en passant square 'z9'"
+        );
+    }
+
+    #[test]
+    fn test_white_kingside_castling() {
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+        assert!(board.get_legal_moves(Square::E1).contains(Square::G1));
+        board.make_move(Square::E1, Square::G1).unwrap();
+
+        assert_eq!(
+            board.pieces(PieceType::King, Color::White),
+            Square::G1.bitboard()
+        );
+        assert_eq!(
+            board.pieces(PieceType::Rook, Color::White),
+            Square::A1.bitboard() | Square::F1.bitboard()
+        );
+        assert!(!board.castling_rights.white_kingside);
+        assert!(!board.castling_rights.white_queenside);
+        assert_eq!(board.to_fen(), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+    }
+
+    #[test]
+    fn test_black_queenside_castling() {
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+
+        board.make_move(Square::E8, Square::C8).unwrap();
+
+        assert_eq!(
+            board.pieces(PieceType::King, Color::Black),
+            Square::C8.bitboard()
+        );
+        assert_eq!(
+            board.pieces(PieceType::Rook, Color::Black),
+            Square::D8.bitboard() | Square::H8.bitboard()
+        );
+        assert_eq!(board.to_fen(), "2kr3r/8/8/8/8/8/8/R3K2R w KQ - 1 2");
+    }
+
+    #[test]
+    fn test_castling_blocked_by_piece() {
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/RN2KB1R w KQkq - 0 1").unwrap();
+
+        assert!(((board.get_legal_moves(Square::E1))
+            & (Square::C1.bitboard() | Square::G1.bitboard()))
+        .is_empty());
+        assert_eq!(
+            board.make_move(Square::E1, Square::G1),
+            Err(MoveError::InvalidDestination)
+        );
+        assert_eq!(
+            board.make_move(Square::E1, Square::C1),
+            Err(MoveError::InvalidDestination)
+        );
+    }
+
+    #[test]
+    fn test_castling_refused_while_in_check() {
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/4r3/R3K2R w KQkq - 0 1").unwrap();
+
+        assert!(((board.get_legal_moves(Square::E1))
+            & (Square::C1.bitboard() | Square::G1.bitboard()))
+        .is_empty());
+    }
+
+    #[test]
+    fn test_castling_refused_through_attacked_square() {
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1").unwrap();
+
+        assert!(!board.get_legal_moves(Square::E1).contains(Square::G1));
+        assert!(board.get_legal_moves(Square::E1).contains(Square::C1));
+    }
+
+    #[test]
+    fn test_queenside_castling_allowed_with_attacked_b_file() {
+        let board = Board::from_fen("1r2k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+
+        assert!(board.get_legal_moves(Square::E1).contains(Square::C1));
+    }
+
+    #[test]
+    fn test_castling_rights_lost_after_king_move() {
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+        board.make_move(Square::E1, Square::F1).unwrap();
+        board.make_move(Square::E8, Square::D8).unwrap();
+        board.make_move(Square::F1, Square::E1).unwrap();
+
+        assert_eq!(board.castling_rights, CastlingRights::NONE);
+        assert!(((board.get_legal_moves(Square::E1))
+            & (Square::C1.bitboard() | Square::G1.bitboard()))
+        .is_empty());
+    }
+
+    #[test]
+    fn test_castling_rights_lost_after_rook_move() {
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+        board.make_move(Square::H1, Square::H2).unwrap();
+
+        assert!(!board.castling_rights.white_kingside);
+        assert!(board.castling_rights.white_queenside);
+    }
+
+    #[test]
+    fn test_castling_rights_lost_after_rook_captured() {
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+        board.make_move(Square::A1, Square::A8).unwrap();
+
+        assert!(!board.castling_rights.white_queenside);
+        assert!(!board.castling_rights.black_queenside);
+        assert!(board.castling_rights.black_kingside);
+    }
+
+    #[test]
+    fn test_double_push_sets_en_passant_square() {
+        let mut board = Board::new();
+
+        board.make_move(Square::E2, Square::E4).unwrap();
+        assert_eq!(board.en_passant_square, Some(Square::E3));
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+
+        board.make_move(Square::B8, Square::C6).unwrap();
+        assert_eq!(board.en_passant_square, None);
+    }
+
+    #[test]
+    fn test_white_en_passant_capture() {
+        let mut board =
+            Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+                .unwrap();
+
+        assert!(board.get_legal_moves(Square::E5).contains(Square::F6));
+        board.make_move(Square::E5, Square::F6).unwrap();
+
+        assert!(board
+            .pieces(PieceType::Pawn, Color::White)
+            .contains(Square::F6));
+        assert!(!board
+            .pieces(PieceType::Pawn, Color::Black)
+            .contains(Square::F5));
+        assert!(board
+            .pieces(PieceType::Pawn, Color::Black)
+            .contains(Square::D5));
+        assert_eq!(board.en_passant_square, None);
+    }
+
+    #[test]
+    fn test_black_en_passant_capture() {
+        let mut board = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+
+        board.make_move(Square::D4, Square::E3).unwrap();
+
+        assert_eq!(
+            board.pieces(PieceType::Pawn, Color::Black),
+            Square::E3.bitboard()
+        );
+        assert!(board.pieces(PieceType::Pawn, Color::White).is_empty());
+        assert_eq!(board.halfmove_clock, 0);
+    }
+
+    #[test]
+    fn test_en_passant_expires_after_one_move() {
+        let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+
+        board.make_move(Square::E1, Square::F1).unwrap();
+        board.make_move(Square::E8, Square::D8).unwrap();
+
+        assert!(!board.get_legal_moves(Square::E5).contains(Square::D6));
+        assert_eq!(
+            board.make_move(Square::E5, Square::D6),
+            Err(MoveError::InvalidDestination)
+        );
+    }
+
+    #[test]
+    fn test_en_passant_refused_when_exposing_king_on_rank() {
+        let board = Board::from_fen("8/8/8/KPp4r/8/8/8/4k3 w - c6 0 1").unwrap();
+
+        assert!(!board.get_legal_moves(Square::B5).contains(Square::C6));
+        assert!(board.get_legal_moves(Square::B5).contains(Square::B6));
+    }
+
+    #[test]
+    fn test_promotion_to_queen() {
+        let mut board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+
+        board
+            .make_move_with_promotion(Square::A7, Square::A8, PieceType::Queen)
+            .unwrap(); // a7 to a8=Q
+
+        assert!(board.pieces(PieceType::Pawn, Color::White).is_empty());
+        assert_eq!(
+            board.pieces(PieceType::Queen, Color::White),
+            Square::A8.bitboard()
+        );
+        assert_eq!(board.to_fen(), "Q3k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    }
+
+    #[test]
+    fn test_black_promotion_to_knight() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/6p1/K7 b - - 0 1").unwrap();
+
+        board
+            .make_move_with_promotion(Square::G2, Square::G1, PieceType::Knight)
+            .unwrap(); // g2 to g1=N
+
+        assert!(board.pieces(PieceType::Pawn, Color::Black).is_empty());
+        assert_eq!(
+            board.pieces(PieceType::Knight, Color::Black),
+            Square::G1.bitboard()
+        );
+        assert_eq!(board.fullmove_number, 2);
+    }
+
+    #[test]
+    fn test_promotion_requires_choice() {
+        let mut board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+
+        assert_eq!(
+            board.make_move(Square::A7, Square::A8),
+            Err(MoveError::InvalidPromotion)
+        );
+        assert_eq!(
+            board.pieces(PieceType::Pawn, Color::White),
+            Square::A7.bitboard()
+        );
+        assert_eq!(board.side_to_move, Color::White);
+    }
+
+    #[test]
+    fn test_promotion_rejects_invalid_piece() {
+        let mut board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+
+        assert_eq!(
+            board.make_move_with_promotion(Square::A7, Square::A8, PieceType::King),
+            Err(MoveError::InvalidPromotion)
+        );
+        assert_eq!(
+            board.make_move_with_promotion(Square::A7, Square::A8, PieceType::Pawn),
+            Err(MoveError::InvalidPromotion)
+        );
+    }
+
+    #[test]
+    fn test_promotion_rejected_for_regular_move() {
+        let mut board = Board::new();
+
+        assert_eq!(
+            board.make_move_with_promotion(Square::E2, Square::E4, PieceType::Queen),
+            Err(MoveError::InvalidPromotion)
+        );
+    }
+
+    #[test]
+    fn test_pawn_push_blocked_by_every_piece_type() {
+        for blocker in ['n', 'b', 'r', 'q', 'N', 'B', 'R', 'Q'] {
+            let fen = format!("7k/8/8/8/8/4{}3/4P3/K7 w - - 0 1", blocker);
+            let mut board = Board::from_fen(&fen).unwrap();
+
+            assert!(
+                board.get_legal_moves(Square::E2).is_empty(),
+                "blocked by {}",
+                blocker
+            );
+            assert!(board.make_move(Square::E2, Square::E3).is_err());
+            assert!(board.make_move(Square::E2, Square::E4).is_err());
+        }
+
+        let board = Board::from_fen("8/8/8/8/4k3/8/4P3/K7 w - - 0 1").unwrap();
+        assert_eq!(board.get_legal_moves(Square::E2), Square::E3.bitboard());
+    }
+
+    #[test]
+    fn test_pawn_captures_every_piece_type() {
+        let targets = [
+            ('p', PieceType::Pawn),
+            ('n', PieceType::Knight),
+            ('b', PieceType::Bishop),
+            ('r', PieceType::Rook),
+            ('q', PieceType::Queen),
+        ];
+
+        for (symbol, piece_type) in targets {
+            let fen = format!("4k3/8/8/3{}4/4P3/8/8/K7 w - - 0 1", symbol);
+            let mut board = Board::from_fen(&fen).unwrap();
+
+            assert_eq!(
+                board.piece_at(Square::D5),
+                Some(Piece::new(piece_type, Color::Black))
+            );
+            board.make_move(Square::E4, Square::D5).unwrap();
+            assert_eq!(
+                board.piece_at(Square::D5),
+                Some(Piece::new(PieceType::Pawn, Color::White))
+            );
+            assert_eq!(
+                board.black_pieces(),
+                board.pieces(PieceType::King, Color::Black)
+            );
+        }
+    }
+
+    #[test]
+    fn test_black_pawn_captures_piece() {
+        let mut board = Board::from_fen("4k3/8/8/3p4/4N3/8/8/K7 b - - 0 1").unwrap();
+
+        board.make_move(Square::D5, Square::E4).unwrap();
+
+        assert!(board.pieces(PieceType::Knight, Color::White).is_empty());
+        assert_eq!(
+            board.pieces(PieceType::Pawn, Color::Black),
+            Square::E4.bitboard()
+        );
+    }
+
+    #[test]
+    fn test_move_into_check_is_rejected() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/3r4/4K3 w - - 0 1").unwrap();
+
+        assert_eq!(
+            board.make_move(Square::E1, Square::E2),
+            Err(MoveError::LeavesKingInCheck)
+        );
+        assert_eq!(
+            board.pieces(PieceType::King, Color::White),
+            Square::E1.bitboard()
+        );
+        assert_eq!(board.side_to_move, Color::White);
+        assert!(board.make_move(Square::E1, Square::D2).is_ok());
+    }
+
+    #[test]
+    fn test_pinned_piece_cannot_leave_pin() {
+        let mut board = Board::from_fen("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+
+        assert!(board.get_legal_moves(Square::E2).is_empty());
+        assert!(!board.get_pseudo_legal_moves(Square::E2).is_empty());
+        assert_eq!(
+            board.make_move(Square::E2, Square::F4),
+            Err(MoveError::LeavesKingInCheck)
+        );
+    }
+
+    #[test]
+    fn test_check_must_be_answered() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/PPP5/1K2r2R w - - 0 1").unwrap();
+
+        assert!(board.get_legal_moves(Square::A2).is_empty());
+        assert!(board.get_legal_moves(Square::B1).is_empty());
+        assert_eq!(board.get_legal_moves(Square::H1), Square::E1.bitboard());
+    }
+
+    #[test]
+    fn test_king_cannot_approach_enemy_king() {
+        let board = Board::from_fen("8/8/8/4k3/8/4K3/8/8 w - - 0 1").unwrap();
+
+        let forbidden: Bitboard = [Square::D4, Square::E4, Square::F4].into_iter().collect();
+        assert!((board.get_legal_moves(Square::E3) & forbidden).is_empty());
+        assert_eq!(board.get_legal_moves(Square::E3).count(), 5);
+    }
+
+    #[test]
+    fn test_generate_moves_starting_position() {
+        let moves = Board::new().generate_moves();
+
+        assert_eq!(moves.len(), 20);
+        assert_eq!(moves.iter().filter(|mv| mv.is_double_push()).count(), 8);
+        assert!(moves.iter().all(|mv| !mv.is_capture()));
+    }
+
+    #[test]
+    fn test_generate_moves_flags() {
+        let board = Board::from_fen("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+        let moves = board.generate_moves();
+
+        let en_passant = moves
+            .iter()
+            .find(|mv| mv.from() == Square::E5 && mv.to() == Square::D6)
+            .unwrap();
+        assert!(en_passant.is_en_passant());
+        assert!(en_passant.is_capture());
+
+        let castles: Vec<_> = moves.iter().filter(|mv| mv.is_castling()).collect();
+        assert_eq!(castles.len(), 2);
+
+        let promotions: Vec<_> = moves.iter().filter(|mv| mv.from() == Square::B7).collect();
+        assert_eq!(promotions.len(), 8); // b8 and b7xa8, four pieces each
+        assert!(promotions.iter().all(|mv| mv.promotion().is_some()));
+        assert_eq!(promotions.iter().filter(|mv| mv.is_capture()).count(), 4);
+    }
+
+    #[test]
+    fn test_generate_moves_checkmate_is_empty() {
+        let board = Board::from_fen("7k/8/8/8/8/8/rr6/K7 w - - 0 1").unwrap();
+
+        assert!(board.generate_moves().is_empty());
+    }
+
+    #[test]
+    fn test_play_generated_move() {
+        let mut board = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let underpromotion = *board
+            .generate_moves()
+            .iter()
+            .find(|mv| mv.promotion() == Some(PieceType::Rook))
+            .unwrap();
+
+        board.play_move(underpromotion).unwrap();
+
+        assert_eq!(board.to_fen(), "1R2k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    }
+
+    #[test]
+    fn test_unmake_restores_capture() {
+        let original =
+            Board::from_fen("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2")
+                .unwrap();
+        let mut board = original.clone();
+
+        board.make_move(Square::E4, Square::D5).unwrap();
+        let undone = board.unmake_move().unwrap();
+
+        assert_eq!((undone.from(), undone.to()), (Square::E4, Square::D5));
+        assert!(undone.is_capture());
+        assert_eq!(board, original);
+        assert_eq!(board.to_fen(), original.to_fen());
+    }
+
+    #[test]
+    fn test_unmake_restores_special_moves() {
+        let fens_and_moves = [
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 3 10",
+                Square::E1,
+                Square::G1,
+                None,
+            ),
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 3 10",
+                Square::E8,
+                Square::C8,
+                None,
+            ),
+            (
+                "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+                Square::E5,
+                Square::D6,
+                None,
+            ),
+            (
+                "r3k3/1P6/8/8/8/8/8/4K3 w q - 0 1",
+                Square::B7,
+                Square::A8,
+                Some(PieceType::Knight),
+            ),
+        ];
+
+        for (fen, from, to, promotion) in fens_and_moves {
+            let original = Board::from_fen(fen).unwrap();
+            let mut board = original.clone();
+
+            match promotion {
+                Some(piece_type) => board.make_move_with_promotion(from, to, piece_type),
+                None => board.make_move(from, to),
+            }
+            .unwrap();
+            assert_ne!(board.to_fen(), original.to_fen());
+
+            board.unmake_move().unwrap();
+            assert_eq!(board, original, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_unmake_with_empty_history() {
+        let mut board = Board::new();
+
+        assert_eq!(board.unmake_move(), None);
+        assert_eq!(board, Board::new());
+    }
+
+    #[test]
+    fn test_make_unmake_identity_over_random_games() {
+        let mut seed = 0x9E37_79B9_7F4A_7C15u64;
+        let mut next_random = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+
+        for _ in 0..20 {
+            let mut board = Board::new();
+            let mut positions = vec![board.clone()];
+
+            for _ in 0..80 {
+                let moves = board.generate_moves();
+                if moves.is_empty() {
+                    break;
+                }
+
+                let mv = moves[(next_random() % moves.len() as u64) as usize];
+                let before = board.clone();
+
+                board.play_move(mv).unwrap();
+                assert_eq!(board.unmake_move(), Some(mv));
+                assert_eq!(board, before, "make/unmake of {} changed the board", mv);
+
+                board.play_move(mv).unwrap();
+                positions.push(board.clone());
+            }
+
+            positions.pop();
+            while let Some(expected) = positions.pop() {
+                board.unmake_move().unwrap();
+                assert_eq!(board, expected);
+            }
+            assert_eq!(board.history_len(), 0);
+        }
+    }
+
+    #[test]
+    fn test_king_cannot_retreat_along_checking_ray() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/r2K4 w - - 0 1").unwrap();
+        let moves = board.get_legal_moves(Square::D1);
+
+        assert!(!moves.contains(Square::C1) && !moves.contains(Square::E1));
+        assert_eq!(
+            moves,
+            [Square::C2, Square::D2, Square::E2].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn test_king_cannot_capture_defended_piece() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/3q3r/3K4 w - - 0 1").unwrap();
+
+        assert!(!board.get_legal_moves(Square::D1).contains(Square::D2));
+    }
+
+    #[test]
+    fn test_mailbox_follows_special_moves() {
+        let mut board = Board::from_fen("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+
+        board.make_move(Square::E5, Square::D6).unwrap();
+        assert_eq!(
+            board.piece_at(Square::D6),
+            Some(Piece::new(PieceType::Pawn, Color::White))
+        );
+        assert_eq!(board.piece_at(Square::D5), None);
+
+        board.make_move(Square::E8, Square::G8).unwrap();
+        assert_eq!(
+            board.piece_at(Square::F8),
+            Some(Piece::new(PieceType::Rook, Color::Black))
+        );
+        assert_eq!(board.piece_at(Square::H8), None);
+
+        board
+            .make_move_with_promotion(Square::B7, Square::B8, PieceType::Queen)
+            .unwrap();
+        assert_eq!(
+            board.piece_at(Square::B8),
+            Some(Piece::new(PieceType::Queen, Color::White))
+        );
+        assert!(board.mailbox_matches_bitboards());
+    }
+
+    #[test]
+    fn test_consistency_check_detects_desync() {
+        let mut board = Board::new();
+        assert!(board.mailbox_matches_bitboards());
+
+        board.mailbox[Square::E2.index()] = None;
+        assert!(!board.mailbox_matches_bitboards());
+
+        let mut board = Board::new();
+        board.pieces[Color::White as usize][PieceType::Queen as usize].insert(Square::E4);
+        assert!(!board.mailbox_matches_bitboards());
+    }
+}
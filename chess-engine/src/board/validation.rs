@@ -0,0 +1,361 @@
+use super::{Board, PIECE_TYPES};
+use crate::bitboard::constants::{RANK_1, RANK_8};
+use crate::bitboard::{Bitboard, Square};
+use crate::game_state::check;
+use crate::pieces::piece_type::{Color, Piece, PieceType};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PositionError {
+    MissingKing(Color),
+    TooManyKings(Color),
+    TooManyPawns(Color),
+    TooManyPieces(Color),
+    PawnOnBackRank(Square),
+    OverlappingPieces(Square),
+    OpponentInCheck(Color),
+    InvalidCastlingRights { color: Color, kingside: bool },
+    InvalidEnPassantSquare(Square),
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionError::MissingKing(color) => write!(f, "{} has no king", color),
+            PositionError::TooManyKings(color) => write!(f, "{} has more than one king", color),
+            PositionError::TooManyPawns(color) => {
+                write!(f, "{} has more than 8 pawns", color)
+            }
+            PositionError::TooManyPieces(color) => {
+                write!(f, "{} has more than 16 pieces", color)
+            }
+            PositionError::PawnOnBackRank(square) => write!(f, "pawn on back rank at {}", square),
+            PositionError::OverlappingPieces(square) => {
+                write!(f, "more than one piece on {}", square)
+            }
+            PositionError::OpponentInCheck(color) => {
+                write!(f, "{} is in check but it is not their move", color)
+            }
+            PositionError::InvalidCastlingRights { color, kingside } => write!(
+                f,
+                "{} may not castle {} without king and rook on their home squares",
+                color,
+                if *kingside { "kingside" } else { "queenside" }
+            ),
+            PositionError::InvalidEnPassantSquare(square) => {
+                write!(f, "no pawn could have just skipped over {}", square)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+impl Board {
+    pub fn validate(&self) -> Result<(), Vec<PositionError>> {
+        let mut errors = Vec::new();
+
+        let mut occupied = Bitboard::EMPTY;
+        for color in [Color::White, Color::Black] {
+            for piece_type in PIECE_TYPES {
+                let pieces = self.pieces(piece_type, color);
+                errors.extend(
+                    (occupied & pieces)
+                        .iter()
+                        .map(PositionError::OverlappingPieces),
+                );
+                occupied |= pieces;
+            }
+        }
+
+        for color in [Color::White, Color::Black] {
+            match self.pieces(PieceType::King, color).count() {
+                0 => errors.push(PositionError::MissingKing(color)),
+                1 => {}
+                _ => errors.push(PositionError::TooManyKings(color)),
+            }
+            if self.pieces(PieceType::Pawn, color).count() > 8 {
+                errors.push(PositionError::TooManyPawns(color));
+            }
+            if self.occupancy(color).count() > 16 {
+                errors.push(PositionError::TooManyPieces(color));
+            }
+        }
+
+        let pawns =
+            self.pieces(PieceType::Pawn, Color::White) | self.pieces(PieceType::Pawn, Color::Black);
+        errors.extend(
+            (pawns & Bitboard(RANK_1 | RANK_8))
+                .iter()
+                .map(PositionError::PawnOnBackRank),
+        );
+
+        let opponent = self.side_to_move.opposite();
+        if self.pieces(PieceType::King, opponent).count() == 1 && check::is_in_check(self, opponent)
+        {
+            errors.push(PositionError::OpponentInCheck(opponent));
+        }
+
+        for (color, kingside) in [
+            (Color::White, true),
+            (Color::White, false),
+            (Color::Black, true),
+            (Color::Black, false),
+        ] {
+            let rook_square = match self.castling_rook(color, kingside) {
+                Some(square) => square,
+                None => continue,
+            };
+            let king_square = Square::from_file_rank(self.castling_files.king, rook_square.rank());
+
+            let has_king = self.piece_at(king_square) == Some(Piece::new(PieceType::King, color));
+            let has_rook = self.piece_at(rook_square) == Some(Piece::new(PieceType::Rook, color));
+            if !(has_king && has_rook) {
+                errors.push(PositionError::InvalidCastlingRights { color, kingside });
+            }
+        }
+
+        if let Some(square) = self.en_passant_square {
+            if !self.is_valid_en_passant_square(square) {
+                errors.push(PositionError::InvalidEnPassantSquare(square));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // The pawn that just moved two squares must sit in front of the en passant
+    // square, with the square it skipped and the square it left both empty.
+    fn is_valid_en_passant_square(&self, square: Square) -> bool {
+        let rank = match self.side_to_move {
+            Color::White => 5,
+            Color::Black => 2,
+        };
+        // Checked first: on the edge ranks the squares below do not exist.
+        if square.rank() != rank {
+            return false;
+        }
+        let (pawn_square, origin) = match self.side_to_move {
+            Color::White => (square.index() - 8, square.index() + 8),
+            Color::Black => (square.index() + 8, square.index() - 8),
+        };
+
+        let pusher = Piece::new(PieceType::Pawn, self.side_to_move.opposite());
+        self.piece_at(Square::new(pawn_square)) == Some(pusher)
+            && self.piece_at(square).is_none()
+            && self.piece_at(Square::new(origin)).is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardBuilder, CastlingFiles, CastlingRights, FenErrorKind};
+
+    #[test]
+    fn test_standard_positions_are_valid() {
+        assert_eq!(Board::new().validate(), Ok(()));
+        assert_eq!(
+            Board::from_fen("rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 2")
+                .map(|board| board.validate()),
+            Ok(Ok(()))
+        );
+    }
+
+    #[test]
+    fn test_king_count() {
+        let errors = Board::from_fen_unchecked("8/8/8/8/8/8/8/K1K5 w - - 0 1")
+            .unwrap()
+            .validate()
+            .unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                PositionError::TooManyKings(Color::White),
+                PositionError::MissingKing(Color::Black),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pawn_on_back_rank() {
+        let errors = Board::from_fen_unchecked("3Pk3/8/8/8/8/8/8/p3K3 w - - 0 1")
+            .unwrap()
+            .validate()
+            .unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                PositionError::PawnOnBackRank(Square::A1),
+                PositionError::PawnOnBackRank(Square::D8),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_too_many_pawns_and_pieces() {
+        let errors = Board::from_fen_unchecked("4k3/8/8/8/P7/NNNNNNNN/PPPPPPPP/4K3 w - - 0 1")
+            .unwrap()
+            .validate()
+            .unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                PositionError::TooManyPawns(Color::White),
+                PositionError::TooManyPieces(Color::White),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_side_not_to_move_in_check() {
+        let errors = Board::from_fen_unchecked("4k3/8/8/8/8/8/8/4RK2 w - - 0 1")
+            .unwrap()
+            .validate()
+            .unwrap_err();
+
+        assert_eq!(errors, vec![PositionError::OpponentInCheck(Color::Black)]);
+    }
+
+    #[test]
+    fn test_castling_rights_need_king_and_rook() {
+        let errors = Board::from_fen_unchecked("r3k3/8/8/8/8/8/8/4K2R w KQkq - 0 1")
+            .unwrap()
+            .validate()
+            .unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                PositionError::InvalidCastlingRights {
+                    color: Color::White,
+                    kingside: false,
+                },
+                PositionError::InvalidCastlingRights {
+                    color: Color::Black,
+                    kingside: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_en_passant_square_needs_pushed_pawn() {
+        for fen in [
+            "4k3/8/8/8/8/8/8/4K3 w - e6 0 1",
+            "4k3/8/8/4p3/8/8/8/4K3 b - e6 0 1",
+            "4k3/4p3/8/4p3/8/8/8/4K3 w - e6 0 1",
+        ] {
+            let errors = Board::from_fen_unchecked(fen)
+                .unwrap()
+                .validate()
+                .unwrap_err();
+            assert_eq!(
+                errors,
+                vec![PositionError::InvalidEnPassantSquare(Square::E6)],
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn test_builder_chess960_castling_files() {
+        let files = CastlingFiles {
+            king: 1,
+            kingside_rook: 4,
+            queenside_rook: 0,
+        };
+        let rights = CastlingRights {
+            white_kingside: true,
+            white_queenside: true,
+            ..CastlingRights::NONE
+        };
+        let white = |piece_type| Piece::new(piece_type, Color::White);
+        let builder = BoardBuilder::new()
+            .piece(Square::A1, white(PieceType::Rook))
+            .piece(Square::B1, white(PieceType::King))
+            .piece(Square::E1, white(PieceType::Rook))
+            .piece(Square::B8, Piece::new(PieceType::King, Color::Black))
+            .castling_rights(rights);
+
+        let board = builder.clone().castling_files(files).build().unwrap();
+        let fen = Board::from_fen("1k6/8/8/8/8/8/8/RK2R3 w EA - 0 1").unwrap();
+        assert!(board.chess960);
+        assert_eq!(board.to_shredder_fen(), fen.to_shredder_fen());
+        assert_eq!(board.zobrist_key(), fen.zobrist_key());
+
+        // On the standard files the same rights have no king and rooks to use.
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn test_en_passant_square_on_the_edge_ranks() {
+        for (square, side_to_move) in [(Square::A1, Color::White), (Square::H8, Color::Black)] {
+            let errors = BoardBuilder::new()
+                .piece(Square::E1, Piece::new(PieceType::King, Color::White))
+                .piece(Square::E8, Piece::new(PieceType::King, Color::Black))
+                .side_to_move(side_to_move)
+                .en_passant_square(Some(square))
+                .build()
+                .unwrap_err();
+            assert_eq!(errors, vec![PositionError::InvalidEnPassantSquare(square)]);
+        }
+    }
+
+    #[test]
+    fn test_overlapping_pieces() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        board.pieces[Color::White as usize][PieceType::Knight as usize].insert(Square::E8);
+
+        assert_eq!(
+            board.validate(),
+            Err(vec![PositionError::OverlappingPieces(Square::E8)])
+        );
+    }
+
+    #[test]
+    fn test_fen_import_rejects_invalid_positions() {
+        let error = Board::from_fen("8/8/8/8/8/8/8/4K3 w - - 0 1").unwrap_err();
+
+        assert_eq!(
+            error.kind,
+            FenErrorKind::InvalidPosition(vec![PositionError::MissingKing(Color::Black)])
+        );
+        assert_eq!(
+            error.to_string(),
+            "invalid FEN \"8/8/8/8/8/8/8/4K3 w - - 0 1\": illegal position: Black has no king"
+        );
+    }
+
+    #[test]
+    fn test_builder_validates() {
+        let board = BoardBuilder::new()
+            .piece(Square::E1, Piece::new(PieceType::King, Color::White))
+            .piece(Square::E8, Piece::new(PieceType::King, Color::Black))
+            .piece(Square::E2, Piece::new(PieceType::Pawn, Color::White))
+            .side_to_move(Color::Black)
+            .build()
+            .unwrap();
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/4P3/4K3 b - - 0 1");
+        assert_eq!(
+            board.zobrist_key(),
+            Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1")
+                .unwrap()
+                .zobrist_key()
+        );
+
+        let errors = BoardBuilder::new()
+            .piece(Square::E1, Piece::new(PieceType::King, Color::White))
+            .build()
+            .unwrap_err();
+        assert_eq!(errors, vec![PositionError::MissingKing(Color::Black)]);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::board::Board;
+use crate::pieces::piece_type::{Color, Piece, PieceType};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+pub const POSITION_COUNT: u32 = 960;
+pub const STANDARD_POSITION: u32 = 518;
+
+// The ten ways to place two knights on the five files left after the bishops
+// and queen, in Scharnagl's order.
+const KNIGHT_PLACEMENTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+// White's back rank for a start position in the standard 0..960 numbering,
+// where 518 is the normal chess setup.
+pub fn back_rank(index: u32) -> Option<[PieceType; 8]> {
+    if index >= POSITION_COUNT {
+        return None;
+    }
+
+    let mut rank: [Option<PieceType>; 8] = [None; 8];
+    let mut n = index as usize;
+
+    rank[n % 4 * 2 + 1] = Some(PieceType::Bishop);
+    n /= 4;
+    rank[n % 4 * 2] = Some(PieceType::Bishop);
+    n /= 4;
+
+    let queen = n % 6;
+    n /= 6;
+    let (first_knight, second_knight) = KNIGHT_PLACEMENTS[n];
+
+    let empty = |rank: &[Option<PieceType>; 8]| -> Vec<usize> {
+        (0..8).filter(|&file| rank[file].is_none()).collect()
+    };
+    rank[empty(&rank)[queen]] = Some(PieceType::Queen);
+
+    let free = empty(&rank);
+    rank[free[first_knight]] = Some(PieceType::Knight);
+    rank[free[second_knight]] = Some(PieceType::Knight);
+
+    let free = empty(&rank);
+    for (file, piece_type) in
+        free.into_iter()
+            .zip([PieceType::Rook, PieceType::King, PieceType::Rook])
+    {
+        rank[file] = Some(piece_type);
+    }
+
+    Some(rank.map(|piece_type| piece_type.expect("every file is filled")))
+}
+
+pub fn start_fen(index: u32) -> Option<String> {
+    let white: String = back_rank(index)?
+        .iter()
+        .map(|&piece_type| Piece::new(piece_type, Color::White).fen_symbol())
+        .collect();
+    let black = white.to_ascii_lowercase();
+
+    Some(format!(
+        "{}/pppppppp/8/8/8/8/PPPPPPPP/{} w KQkq - 0 1",
+        black, white
+    ))
+}
+
+pub fn start_position(index: u32) -> Option<Board> {
+    let fen = start_fen(index)?;
+    let mut board = Board::from_fen(&fen).expect("Chess960 start positions are valid");
+    board.chess960 = true;
+    Some(board)
+}
+
+// Every RandomState is freshly keyed, so hashing nothing is already random.
+pub fn random_index() -> u32 {
+    let hasher = RandomState::new().build_hasher();
+    (hasher.finish() % POSITION_COUNT as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::Square;
+    use crate::board::{CastlingFiles, STARTING_FEN};
+    use crate::movement::san::{move_to_san, parse_san};
+    use crate::perft::perft;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_known_indices() {
+        assert_eq!(start_fen(STANDARD_POSITION).as_deref(), Some(STARTING_FEN));
+        assert_eq!(
+            start_fen(0).as_deref(),
+            Some("bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1")
+        );
+        assert_eq!(
+            start_fen(959).as_deref(),
+            Some("rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1")
+        );
+        assert_eq!(start_fen(POSITION_COUNT), None);
+    }
+
+    #[test]
+    fn test_every_index_is_a_distinct_legal_setup() {
+        let mut seen = HashSet::new();
+
+        for index in 0..POSITION_COUNT {
+            let rank = back_rank(index).unwrap();
+            let file_of = |piece_type| {
+                (0..8)
+                    .filter(|&file| rank[file] == piece_type)
+                    .collect::<Vec<_>>()
+            };
+
+            let bishops = file_of(PieceType::Bishop);
+            let rooks = file_of(PieceType::Rook);
+            let king = file_of(PieceType::King)[0];
+            assert_ne!(bishops[0] % 2, bishops[1] % 2, "index {}", index);
+            assert!(rooks[0] < king && king < rooks[1], "index {}", index);
+            assert!(seen.insert(rank));
+        }
+
+        let index = random_index();
+        assert!(index < POSITION_COUNT);
+    }
+
+    #[test]
+    fn test_start_position_castling_setup() {
+        let board = start_position(0).unwrap();
+
+        assert!(board.chess960);
+        assert_eq!(
+            board.castling_files,
+            CastlingFiles {
+                king: 6,
+                kingside_rook: 7,
+                queenside_rook: 5,
+            }
+        );
+        assert_eq!(
+            board.to_shredder_fen(),
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1"
+        );
+        assert!(start_position(STANDARD_POSITION).unwrap().chess960);
+        assert!(!Board::new().chess960);
+    }
+
+    #[test]
+    fn test_castling_with_king_already_on_its_destination() {
+        let mut board =
+            Board::from_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9")
+                .unwrap();
+
+        // The king stays on g1 and the h1 rook jumps to f1, so f1 must be empty.
+        assert!(!board.get_legal_moves(Square::G1).contains(Square::H1));
+
+        board = Board::from_fen("bqnb1rk1/pp3ppr/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BN1KR w H - 0 9")
+            .unwrap();
+        let castle = parse_san(&board, "O-O").unwrap();
+        assert_eq!((castle.from(), castle.to()), (Square::G1, Square::H1));
+        assert_eq!(castle.to_string(), "g1h1");
+
+        board.play_move(castle).unwrap();
+        assert_eq!(
+            board.to_shredder_fen(),
+            "bqnb1rk1/pp3ppr/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRK1 b - - 1 9"
+        );
+        board.unmake_move();
+        assert_eq!(move_to_san(&board, castle), "O-O");
+    }
+
+    #[test]
+    fn test_queenside_castling_past_the_rook() {
+        // King on b1, rook on a1: the king moves right to c1 and the rook to d1.
+        let mut board = Board::from_fen("rk6/8/8/8/8/8/8/RK6 w Aa - 0 1").unwrap();
+        board
+            .play_move(parse_san(&board, "O-O-O").unwrap())
+            .unwrap();
+
+        assert_eq!(board.to_fen(), "rk6/8/8/8/8/8/8/2KR4 b q - 1 1");
+    }
+
+    #[test]
+    fn test_castling_rook_cannot_uncover_check() {
+        // The b1 rook shields c1 from the a1 queen until it castles away.
+        let board = Board::from_fen("6k1/8/8/8/8/8/8/qRK4R w B - 0 1").unwrap();
+
+        assert!(board.generate_moves().iter().all(|mv| !mv.is_castling()));
+    }
+
+    #[test]
+    fn test_x_fen_uses_files_only_when_ambiguous() {
+        let board = Board::from_fen("rk2r3/8/8/8/8/8/8/RK2R2R w EAa - 0 1").unwrap();
+
+        assert_eq!(board.to_fen(), "rk2r3/8/8/8/8/8/8/RK2R2R w EQq - 0 1");
+        assert_eq!(
+            Board::from_fen(&board.to_fen()).unwrap().to_shredder_fen(),
+            "rk2r3/8/8/8/8/8/8/RK2R2R w EAa - 0 1"
+        );
+    }
+
+    #[test]
+    fn test_standard_index_plays_like_standard_chess() {
+        let mut board = start_position(STANDARD_POSITION).unwrap();
+
+        assert_eq!(perft(&mut board, 3), 8902);
+        assert_eq!(board.to_fen(), STARTING_FEN);
+    }
+}
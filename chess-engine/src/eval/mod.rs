@@ -0,0 +1,460 @@
+mod pst;
+
+use crate::bitboard::attacks;
+use crate::bitboard::constants::FILE_A;
+use crate::bitboard::{Bitboard, Square};
+use crate::board::Board;
+use crate::pieces::piece_type::{Color, PieceType};
+use crate::pieces::{bishop, knight, queen, rook};
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Sub};
+
+// Every term is scored twice, once for the middlegame and once for the
+// endgame, and the two are blended by how much material is left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Score {
+    mg: i32,
+    eg: i32,
+}
+
+impl Score {
+    const fn new(mg: i32, eg: i32) -> Self {
+        Self { mg, eg }
+    }
+
+    fn taper(self, phase: i32) -> i32 {
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for Score {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.mg + rhs.mg, self.eg + rhs.eg)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Score {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.mg - rhs.mg, self.eg - rhs.eg)
+    }
+}
+
+impl Mul<i32> for Score {
+    type Output = Self;
+
+    fn mul(self, rhs: i32) -> Self {
+        Self::new(self.mg * rhs, self.eg * rhs)
+    }
+}
+
+const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::King,
+    PieceType::Queen,
+];
+
+// Indexed by PieceType, in the order above.
+const MATERIAL: [Score; 6] = [
+    Score::new(100, 120),
+    Score::new(320, 300),
+    Score::new(330, 320),
+    Score::new(500, 530),
+    Score::new(0, 0),
+    Score::new(950, 950),
+];
+
+const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 0, 4];
+const MAX_PHASE: i32 = 24;
+
+// Per square a piece can safely reach beyond a typical count for that piece.
+const MOBILITY: [Score; 6] = [
+    Score::new(0, 0),
+    Score::new(4, 4),
+    Score::new(5, 5),
+    Score::new(2, 4),
+    Score::new(0, 0),
+    Score::new(1, 2),
+];
+const MOBILITY_BASELINE: [i32; 6] = [0, 4, 7, 7, 0, 14];
+
+const DOUBLED_PAWN: Score = Score::new(-10, -20);
+const ISOLATED_PAWN: Score = Score::new(-10, -15);
+// Indexed by rank counted from the pawn's own side.
+const PASSED_PAWN: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(5, 10),
+    Score::new(10, 15),
+    Score::new(15, 25),
+    Score::new(25, 45),
+    Score::new(40, 75),
+    Score::new(60, 110),
+    Score::new(0, 0),
+];
+
+// King safety only matters while there is material left to attack with, so
+// these have no endgame weight.
+const MISSING_SHIELD_PAWN: Score = Score::new(-15, 0);
+const OPEN_FILE_NEAR_KING: Score = Score::new(-20, 0);
+const KING_ATTACK_WEIGHTS: [i32; 6] = [0, 2, 2, 3, 0, 5];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Evaluation {
+    pub material: i32,
+    pub piece_squares: i32,
+    pub mobility: i32,
+    pub pawn_structure: i32,
+    pub king_safety: i32,
+    // From MAX_PHASE with every piece on the board down to 0 with only kings
+    // and pawns left.
+    pub phase: i32,
+}
+
+impl Evaluation {
+    pub fn total(&self) -> i32 {
+        self.material + self.piece_squares + self.mobility + self.pawn_structure + self.king_safety
+    }
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms = [
+            ("Material", self.material),
+            ("Piece squares", self.piece_squares),
+            ("Mobility", self.mobility),
+            ("Pawn structure", self.pawn_structure),
+            ("King safety", self.king_safety),
+        ];
+        for (name, value) in terms {
+            writeln!(f, "{:<16}{:>+6}", name, value)?;
+        }
+        write!(
+            f,
+            "{:<16}{:>+6}  (phase {}/{})",
+            "Total",
+            self.total(),
+            self.phase,
+            MAX_PHASE
+        )
+    }
+}
+
+// Centipawns from the point of view of the side to move.
+pub fn evaluate(board: &Board) -> i32 {
+    breakdown(board).total()
+}
+
+// The terms behind `evaluate`, also from the side to move's point of view.
+pub fn breakdown(board: &Board) -> Evaluation {
+    let phase = game_phase(board);
+    let sign = match board.side_to_move {
+        Color::White => 1,
+        Color::Black => -1,
+    };
+    let term = |score: fn(&Board, Color) -> Score| {
+        sign * (score(board, Color::White) - score(board, Color::Black)).taper(phase)
+    };
+
+    Evaluation {
+        material: term(material),
+        piece_squares: term(piece_squares),
+        mobility: term(mobility),
+        pawn_structure: term(pawn_structure),
+        king_safety: term(king_safety),
+        phase,
+    }
+}
+
+fn game_phase(board: &Board) -> i32 {
+    let phase: i32 = PIECE_TYPES
+        .iter()
+        .map(|&piece_type| {
+            let count = board.pieces(piece_type, Color::White).count()
+                + board.pieces(piece_type, Color::Black).count();
+            PHASE_WEIGHTS[piece_type as usize] * count as i32
+        })
+        .sum();
+    phase.min(MAX_PHASE)
+}
+
+fn material(board: &Board, color: Color) -> Score {
+    let mut score = Score::default();
+    for piece_type in PIECE_TYPES {
+        score += MATERIAL[piece_type as usize] * board.pieces(piece_type, color).count() as i32;
+    }
+    score
+}
+
+fn piece_squares(board: &Board, color: Color) -> Score {
+    let mut score = Score::default();
+    for piece_type in PIECE_TYPES {
+        for square in board.pieces(piece_type, color) {
+            score += pst::piece_square(piece_type, color, square);
+        }
+    }
+    score
+}
+
+fn mobility(board: &Board, color: Color) -> Score {
+    let white_pieces = board.white_pieces().0;
+    let black_pieces = board.black_pieces().0;
+
+    // Squares an enemy pawn covers are not worth counting as room to move.
+    let mut pawn_covered = 0;
+    for square in board.pieces(PieceType::Pawn, color.opposite()) {
+        pawn_covered |= attacks::pawn_attacks(color.opposite(), square.index());
+    }
+
+    let mut score = Score::default();
+    for piece_type in [
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ] {
+        let white = board.pieces(piece_type, Color::White).0;
+        let black = board.pieces(piece_type, Color::Black).0;
+        let generate = match piece_type {
+            PieceType::Knight => knight::get_knight_moves,
+            PieceType::Bishop => bishop::get_bishop_moves,
+            PieceType::Rook => rook::get_rook_moves,
+            _ => queen::get_queen_moves,
+        };
+
+        for square in board.pieces(piece_type, color) {
+            let moves = generate(
+                square.index(),
+                white,
+                black,
+                white_pieces,
+                black_pieces,
+                color,
+            );
+            let count = (moves & !pawn_covered).count_ones() as i32;
+            score +=
+                MOBILITY[piece_type as usize] * (count - MOBILITY_BASELINE[piece_type as usize]);
+        }
+    }
+    score
+}
+
+fn pawn_structure(board: &Board, color: Color) -> Score {
+    let pawns = board.pieces(PieceType::Pawn, color).0;
+    let enemy_pawns = board.pieces(PieceType::Pawn, color.opposite()).0;
+    let mut score = Score::default();
+
+    for file in 0..8 {
+        let count = (pawns & file_mask(file)).count_ones() as i32;
+        if count > 1 {
+            score += DOUBLED_PAWN * (count - 1);
+        }
+        if count > 0 && pawns & adjacent_files(file) == 0 {
+            score += ISOLATED_PAWN * count;
+        }
+    }
+
+    for square in Bitboard(pawns) {
+        let ahead = squares_ahead(color, square);
+        let blocked_by_own = pawns & file_mask(square.file()) & ahead != 0;
+        let opposed =
+            enemy_pawns & (file_mask(square.file()) | adjacent_files(square.file())) & ahead;
+        if !blocked_by_own && opposed == 0 {
+            score += PASSED_PAWN[relative_rank(color, square)];
+        }
+    }
+
+    score
+}
+
+fn king_safety(board: &Board, color: Color) -> Score {
+    let king = match board.pieces(PieceType::King, color).lsb() {
+        Some(king) => king,
+        None => return Score::default(),
+    };
+    let pawns = board.pieces(PieceType::Pawn, color).0;
+    let mut score = Score::default();
+
+    // A king that has left its back two ranks is judged by the attack count
+    // alone; the shield only makes sense around a castled or home king.
+    if relative_rank(color, king) <= 1 {
+        let shield_ranks = squares_ahead(color, king) & !squares_ahead_by(color, king, 3);
+        let first_file = king.file().saturating_sub(1);
+        let last_file = (king.file() + 1).min(7);
+        for file in first_file..=last_file {
+            if pawns & file_mask(file) == 0 {
+                score += OPEN_FILE_NEAR_KING;
+            } else if pawns & file_mask(file) & shield_ranks == 0 {
+                score += MISSING_SHIELD_PAWN;
+            }
+        }
+    }
+
+    let zone = attacks::king_attacks(king.index()) | king.bitboard().0;
+    let occupancy = board.all_pieces().0;
+    let enemy = color.opposite();
+    let mut attack_units = 0;
+    for piece_type in [
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ] {
+        for square in board.pieces(piece_type, enemy) {
+            let reach = match piece_type {
+                PieceType::Knight => attacks::knight_attacks(square.index()),
+                PieceType::Bishop => attacks::bishop_attacks(square.index(), occupancy),
+                PieceType::Rook => attacks::rook_attacks(square.index(), occupancy),
+                _ => attacks::queen_attacks(square.index(), occupancy),
+            };
+            if reach & zone != 0 {
+                attack_units += KING_ATTACK_WEIGHTS[piece_type as usize];
+            }
+        }
+    }
+    // Attackers are far more dangerous together than alone.
+    score += Score::new(-(attack_units * attack_units) / 2, 0);
+
+    score
+}
+
+fn file_mask(file: usize) -> u64 {
+    FILE_A << file
+}
+
+fn adjacent_files(file: usize) -> u64 {
+    let mut mask = 0;
+    if file > 0 {
+        mask |= file_mask(file - 1);
+    }
+    if file < 7 {
+        mask |= file_mask(file + 1);
+    }
+    mask
+}
+
+fn relative_rank(color: Color, square: Square) -> usize {
+    match color {
+        Color::White => square.rank(),
+        Color::Black => 7 - square.rank(),
+    }
+}
+
+// Every square on a rank in front of `square` from `color`'s side.
+fn squares_ahead(color: Color, square: Square) -> u64 {
+    squares_ahead_by(color, square, 1)
+}
+
+fn squares_ahead_by(color: Color, square: Square, ranks: usize) -> u64 {
+    match color {
+        Color::White => {
+            let rank = square.rank() + ranks;
+            if rank > 7 {
+                0
+            } else {
+                !0u64 << (rank * 8)
+            }
+        }
+        Color::Black => match square.rank().checked_sub(ranks) {
+            Some(rank) => (1u64 << ((rank + 1) * 8)).wrapping_sub(1),
+            None => 0,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::STARTING_FEN;
+
+    fn eval(fen: &str) -> Evaluation {
+        breakdown(&Board::from_fen(fen).unwrap())
+    }
+
+    #[test]
+    fn test_starting_position_is_balanced() {
+        let evaluation = eval(STARTING_FEN);
+
+        assert_eq!(
+            evaluation,
+            Evaluation {
+                phase: MAX_PHASE,
+                ..Evaluation::default()
+            }
+        );
+        assert_eq!(evaluate(&Board::new()), 0);
+    }
+
+    #[test]
+    fn test_score_is_from_the_side_to_move() {
+        let white = eval("4k3/8/8/8/8/8/4PPPP/R3K3 w - - 0 1");
+        let black = eval("4k3/8/8/8/8/8/4PPPP/R3K3 b - - 0 1");
+        let mirrored = eval("r3k3/4pppp/8/8/8/8/8/4K3 b - - 0 1");
+
+        assert!(white.material > 0);
+        assert_eq!(black.total(), -white.total());
+        assert_eq!(mirrored, white);
+    }
+
+    #[test]
+    fn test_breakdown_sums_to_evaluate() {
+        let fen = "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 0 8";
+        let board = Board::from_fen(fen).unwrap();
+        let evaluation = breakdown(&board);
+
+        assert_eq!(evaluation.total(), evaluate(&board));
+        assert!(evaluation.phase > 0 && evaluation.phase <= MAX_PHASE);
+        assert!(evaluation.to_string().contains("Pawn structure"));
+    }
+
+    #[test]
+    fn test_pawn_structure() {
+        // Doubled and isolated c-pawns against a healthy pair.
+        let weak = eval("4k3/3pp3/8/8/8/2P5/2P5/4K3 w - - 0 1");
+        assert!(weak.pawn_structure < 0);
+
+        // A passed pawn on the sixth is worth more than one on the third.
+        let far = eval("4k3/p7/3P4/8/8/8/P7/4K3 w - - 0 1");
+        let near = eval("4k3/p7/8/8/8/3P4/P7/4K3 w - - 0 1");
+        assert!(far.pawn_structure > near.pawn_structure);
+
+        // An enemy pawn on an adjacent file stops it from being passed.
+        let opposed = eval("4k3/ppp5/8/8/8/3P4/P7/4K3 w - - 0 1");
+        assert!(opposed.pawn_structure < near.pawn_structure);
+    }
+
+    #[test]
+    fn test_king_safety() {
+        let sheltered = eval("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1");
+        assert_eq!(sheltered.king_safety, 0);
+
+        let exposed = eval("r5k1/5ppp/8/8/8/8/8/6K1 w - - 0 1");
+        assert!(exposed.king_safety < sheltered.king_safety);
+
+        let attacked = eval("6k1/5ppp/8/8/6q1/5n2/5PPP/6K1 w - - 0 1");
+        assert!(attacked.king_safety < 0);
+    }
+
+    #[test]
+    fn test_mobility_and_endgame_king() {
+        let free = eval("4k3/8/8/8/3B4/8/8/4K3 w - - 0 1");
+        let boxed = eval("4k3/8/8/8/8/8/1P6/B3K3 w - - 0 1");
+        assert!(free.mobility > boxed.mobility);
+
+        // With nothing but pawns left, a central king is an asset.
+        let central = eval("8/8/8/3k4/8/8/P7/K7 b - - 0 1");
+        assert_eq!(central.phase, 0);
+        assert!(central.piece_squares > 0);
+    }
+}
@@ -0,0 +1,565 @@
+use crate::board::{Board, FenError};
+use crate::game_state::game_status::{get_claimable_draw, get_game_status, DrawReason, GameStatus};
+use crate::movement::moves::Move;
+use crate::movement::san::{parse_san, SanError};
+use crate::pgn::{PgnError, PgnGame};
+use crate::pieces::piece_type::{Color, MoveError};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    Ongoing,
+}
+
+impl GameResult {
+    pub fn win_for(color: Color) -> Self {
+        match color {
+            Color::White => GameResult::WhiteWins,
+            Color::Black => GameResult::BlackWins,
+        }
+    }
+
+    pub fn from_pgn(token: &str) -> Option<Self> {
+        match token {
+            "1-0" => Some(GameResult::WhiteWins),
+            "0-1" => Some(GameResult::BlackWins),
+            "1/2-1/2" => Some(GameResult::Draw),
+            "*" => Some(GameResult::Ongoing),
+            _ => None,
+        }
+    }
+
+    pub fn as_pgn(self) -> &'static str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Ongoing => "*",
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_pgn())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    Draw(DrawReason),
+    DrawAgreement,
+    Resignation(Color),
+}
+
+impl Termination {
+    // The PGN Termination tag only separates games that ended over the board
+    // from ones decided by the clock or an arbiter, which are not modelled here.
+    pub fn as_pgn(self) -> &'static str {
+        "normal"
+    }
+
+    // Whether the position itself ended the game, rather than a player
+    // resigning, agreeing to a draw or claiming one.
+    pub fn is_forced(self) -> bool {
+        match self {
+            Termination::Checkmate | Termination::Stalemate => true,
+            Termination::Draw(reason) => !reason.requires_claim(),
+            Termination::DrawAgreement | Termination::Resignation(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Termination::Checkmate => write!(f, "checkmate"),
+            Termination::Stalemate => write!(f, "stalemate"),
+            Termination::Draw(reason) => write!(f, "{}", reason),
+            Termination::DrawAgreement => write!(f, "agreement"),
+            Termination::Resignation(color) => write!(f, "{} resigned", color),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    GameOver,
+    IllegalMove(MoveError),
+    InvalidSan(SanError),
+    NoDrawOffer,
+    NoDrawToClaim,
+    NoMoveToUndo,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::GameOver => write!(f, "the game is already over"),
+            GameError::IllegalMove(error) => write!(f, "illegal move: {}", error),
+            GameError::InvalidSan(error) => write!(f, "{}", error),
+            GameError::NoDrawOffer => write!(f, "there is no draw offer to accept"),
+            GameError::NoDrawToClaim => write!(f, "no draw can be claimed in this position"),
+            GameError::NoMoveToUndo => write!(f, "there is no move to take back"),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+#[derive(Debug, Clone)]
+pub struct Game {
+    pub white_player: String,
+    pub black_player: String,
+    board: Board,
+    start_fen: String,
+    moves: Vec<Move>,
+    positions: Vec<u64>,
+    result: GameResult,
+    termination: Option<Termination>,
+    draw_offer: Option<Color>,
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Self::from_board(Board::new())
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        Ok(Self::from_board(Board::from_fen(fen)?))
+    }
+
+    pub fn from_board(board: Board) -> Self {
+        let mut start = board.clone();
+        let mut moves = Vec::new();
+        let mut positions = vec![board.zobrist_key()];
+        while let Some(mv) = start.unmake_move() {
+            moves.push(mv);
+            positions.push(start.zobrist_key());
+        }
+        moves.reverse();
+        positions.reverse();
+
+        let mut game = Self {
+            white_player: "?".to_string(),
+            black_player: "?".to_string(),
+            start_fen: start.to_fen(),
+            board,
+            moves,
+            positions,
+            result: GameResult::Ongoing,
+            termination: None,
+            draw_offer: None,
+        };
+        game.update_result();
+        game
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn start_fen(&self) -> &str {
+        &self.start_fen
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    pub fn position_history(&self) -> &[u64] {
+        &self.positions
+    }
+
+    pub fn result(&self) -> GameResult {
+        self.result
+    }
+
+    // `None` while the game is in progress, and for loaded games whose record
+    // gives a result without saying how it came about.
+    pub fn termination(&self) -> Option<Termination> {
+        self.termination
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.result != GameResult::Ongoing
+    }
+
+    pub fn draw_offer(&self) -> Option<Color> {
+        self.draw_offer
+    }
+
+    pub fn play_move(&mut self, mv: Move) -> Result<(), GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+
+        let mover = self.board.side_to_move;
+        self.board.play_move(mv).map_err(GameError::IllegalMove)?;
+        self.moves.push(mv);
+        self.positions.push(self.board.zobrist_key());
+
+        // Moving instead of answering declines the opponent's offer.
+        if self.draw_offer == Some(mover.opposite()) {
+            self.draw_offer = None;
+        }
+
+        self.update_result();
+        Ok(())
+    }
+
+    pub fn play_san(&mut self, san: &str) -> Result<Move, GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+
+        let mv = parse_san(&self.board, san).map_err(GameError::InvalidSan)?;
+        self.play_move(mv)?;
+        Ok(mv)
+    }
+
+    // Taking back a move reopens a game the position ended, but not one the
+    // players decided, or one loaded with a result but no reason for it.
+    pub fn undo_move(&mut self) -> Result<Move, GameError> {
+        if self.is_over() && !self.termination.is_some_and(Termination::is_forced) {
+            return Err(GameError::GameOver);
+        }
+
+        let mv = self.board.unmake_move().ok_or(GameError::NoMoveToUndo)?;
+        self.moves.pop();
+        self.positions.pop();
+        self.draw_offer = None;
+        self.result = GameResult::Ongoing;
+        self.termination = None;
+        self.update_result();
+        Ok(mv)
+    }
+
+    pub fn resign(&mut self, color: Color) -> Result<(), GameError> {
+        self.finish(
+            GameResult::win_for(color.opposite()),
+            Termination::Resignation(color),
+        )
+    }
+
+    pub fn offer_draw(&mut self, color: Color) -> Result<(), GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+
+        self.draw_offer = Some(color);
+        Ok(())
+    }
+
+    pub fn accept_draw(&mut self, color: Color) -> Result<(), GameError> {
+        if self.draw_offer != Some(color.opposite()) {
+            return Err(GameError::NoDrawOffer);
+        }
+
+        self.finish(GameResult::Draw, Termination::DrawAgreement)
+    }
+
+    pub fn decline_draw(&mut self, color: Color) -> Result<(), GameError> {
+        if self.draw_offer != Some(color.opposite()) {
+            return Err(GameError::NoDrawOffer);
+        }
+
+        self.draw_offer = None;
+        Ok(())
+    }
+
+    pub fn claim_draw(&mut self) -> Result<DrawReason, GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+
+        let reason = get_claimable_draw(&self.board).ok_or(GameError::NoDrawToClaim)?;
+        self.finish(GameResult::Draw, Termination::Draw(reason))?;
+        Ok(reason)
+    }
+
+    pub fn to_pgn(&self) -> PgnGame {
+        let mut pgn = PgnGame::from_board(&self.board);
+        pgn.set_tag("White", &self.white_player);
+        pgn.set_tag("Black", &self.black_player);
+        pgn.result = self.result;
+        pgn.set_tag("Result", self.result.as_pgn());
+        if let Some(termination) = self.termination {
+            pgn.set_tag("Termination", termination.as_pgn());
+        }
+        pgn
+    }
+
+    pub fn from_pgn(pgn: &PgnGame) -> Result<Self, PgnError> {
+        let mut game = Self::from_board(pgn.starting_board()?);
+        game.white_player = pgn.tag("White").unwrap_or("?").to_string();
+        game.black_player = pgn.tag("Black").unwrap_or("?").to_string();
+
+        for node in &pgn.moves {
+            let move_number = game.board.fullmove_number;
+            game.play_move(node.mv).map_err(|error| match error {
+                GameError::IllegalMove(error) => PgnError::InvalidMove {
+                    move_number,
+                    san: node.san.clone(),
+                    error,
+                },
+                _ => PgnError::MoveAfterGameOver {
+                    move_number,
+                    san: node.san.clone(),
+                },
+            })?;
+        }
+
+        if !game.is_over() && pgn.result != GameResult::Ongoing {
+            game.result = pgn.result;
+        }
+
+        Ok(game)
+    }
+
+    fn finish(&mut self, result: GameResult, termination: Termination) -> Result<(), GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+
+        self.result = result;
+        self.termination = Some(termination);
+        self.draw_offer = None;
+        Ok(())
+    }
+
+    fn update_result(&mut self) {
+        let (result, termination) = match get_game_status(&self.board) {
+            GameStatus::Checkmate => (
+                GameResult::win_for(self.board.side_to_move.opposite()),
+                Termination::Checkmate,
+            ),
+            GameStatus::Stalemate => (GameResult::Draw, Termination::Stalemate),
+            GameStatus::Draw(reason) => (GameResult::Draw, Termination::Draw(reason)),
+            GameStatus::Ongoing | GameStatus::Check => return,
+        };
+
+        self.result = result;
+        self.termination = Some(termination);
+        self.draw_offer = None;
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgn::parse_pgn;
+
+    fn play_all(game: &mut Game, moves: &[&str]) {
+        for san in moves {
+            game.play_san(san).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_checkmate_ends_the_game() {
+        let mut game = Game::new();
+        play_all(&mut game, &["f3", "e5", "g4", "Qh4#"]);
+
+        assert_eq!(game.result(), GameResult::BlackWins);
+        assert_eq!(game.termination(), Some(Termination::Checkmate));
+        assert_eq!(game.moves().len(), 4);
+        assert_eq!(game.position_history().len(), 5);
+        assert_eq!(game.play_san("Kf2"), Err(GameError::GameOver));
+    }
+
+    #[test]
+    fn test_stalemate_from_fen() {
+        let mut game = Game::from_fen("7k/8/6Q1/8/8/8/8/K7 w - - 0 1").unwrap();
+        assert_eq!(game.start_fen(), "7k/8/6Q1/8/8/8/8/K7 w - - 0 1");
+
+        game.play_san("Qf7").unwrap();
+
+        assert_eq!(game.result(), GameResult::Draw);
+        assert_eq!(game.termination(), Some(Termination::Stalemate));
+    }
+
+    #[test]
+    fn test_resignation() {
+        let mut game = Game::new();
+        game.play_san("e4").unwrap();
+        game.resign(Color::Black).unwrap();
+
+        assert_eq!(game.result(), GameResult::WhiteWins);
+        assert_eq!(
+            game.termination(),
+            Some(Termination::Resignation(Color::Black))
+        );
+        assert_eq!(game.resign(Color::White), Err(GameError::GameOver));
+        assert_eq!(game.termination().unwrap().to_string(), "Black resigned");
+        assert_eq!(
+            GameError::IllegalMove(MoveError::PathBlocked).to_string(),
+            "illegal move: the path is blocked by another piece"
+        );
+    }
+
+    #[test]
+    fn test_draw_offers() {
+        let mut game = Game::new();
+        game.offer_draw(Color::White).unwrap();
+        game.play_san("e4").unwrap();
+
+        assert_eq!(game.accept_draw(Color::White), Err(GameError::NoDrawOffer));
+        assert_eq!(game.draw_offer(), Some(Color::White));
+
+        game.play_san("e5").unwrap();
+        assert_eq!(game.draw_offer(), None);
+        assert_eq!(game.accept_draw(Color::Black), Err(GameError::NoDrawOffer));
+
+        game.offer_draw(Color::White).unwrap();
+        game.decline_draw(Color::Black).unwrap();
+        assert_eq!(game.draw_offer(), None);
+
+        game.offer_draw(Color::White).unwrap();
+        game.accept_draw(Color::Black).unwrap();
+        assert_eq!(game.result(), GameResult::Draw);
+        assert_eq!(game.termination(), Some(Termination::DrawAgreement));
+    }
+
+    #[test]
+    fn test_claim_threefold_repetition() {
+        let mut game = Game::new();
+        assert_eq!(game.claim_draw(), Err(GameError::NoDrawToClaim));
+
+        play_all(
+            &mut game,
+            &["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"],
+        );
+        let start = game.position_history()[0];
+        assert_eq!(
+            game.position_history()
+                .iter()
+                .filter(|&&key| key == start)
+                .count(),
+            3
+        );
+
+        assert_eq!(game.claim_draw(), Ok(DrawReason::ThreefoldRepetition));
+        assert_eq!(
+            game.termination(),
+            Some(Termination::Draw(DrawReason::ThreefoldRepetition))
+        );
+    }
+
+    #[test]
+    fn test_undo_reopens_a_finished_game() {
+        let mut game = Game::new();
+        play_all(&mut game, &["f3", "e5", "g4", "Qh4#"]);
+
+        assert_eq!(
+            game.undo_move().map(|mv| mv.to().to_string()),
+            Ok("h4".to_string())
+        );
+        assert_eq!(game.result(), GameResult::Ongoing);
+        assert_eq!(game.termination(), None);
+        assert_eq!(game.moves().len(), 3);
+        assert_eq!(game.position_history().len(), 4);
+
+        game.undo_move().unwrap();
+        game.undo_move().unwrap();
+        game.undo_move().unwrap();
+        assert_eq!(game.undo_move(), Err(GameError::NoMoveToUndo));
+    }
+
+    #[test]
+    fn test_undo_keeps_games_the_players_decided() {
+        let mut game = Game::new();
+        play_all(&mut game, &["e4", "e5"]);
+        game.resign(Color::White).unwrap();
+        assert_eq!(game.undo_move(), Err(GameError::GameOver));
+        assert_eq!(game.result(), GameResult::BlackWins);
+        assert_eq!(game.moves().len(), 2);
+
+        let mut game = Game::new();
+        game.play_san("e4").unwrap();
+        game.offer_draw(Color::Black).unwrap();
+        game.accept_draw(Color::White).unwrap();
+        assert_eq!(game.undo_move(), Err(GameError::GameOver));
+
+        let mut game = Game::new();
+        play_all(
+            &mut game,
+            &["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"],
+        );
+        game.claim_draw().unwrap();
+        assert_eq!(game.undo_move(), Err(GameError::GameOver));
+
+        let loaded = parse_pgn("1. e4 e5 1-0").unwrap();
+        let mut game = Game::from_pgn(&loaded[0]).unwrap();
+        assert_eq!(game.undo_move(), Err(GameError::GameOver));
+    }
+
+    #[test]
+    fn test_pgn_round_trip() {
+        let mut game = Game::new();
+        game.white_player = "Alice".to_string();
+        game.black_player = "Bob".to_string();
+        play_all(&mut game, &["e4", "e5"]);
+        game.resign(Color::White).unwrap();
+
+        let pgn = game.to_pgn();
+        assert_eq!(pgn.tag("White"), Some("Alice"));
+        assert_eq!(pgn.tag("Termination"), Some("normal"));
+        assert_eq!(game.termination().unwrap().to_string(), "White resigned");
+
+        let loaded = Game::from_pgn(&parse_pgn(&pgn.to_string()).unwrap()[0]).unwrap();
+        assert_eq!(loaded.black_player, "Bob");
+        assert_eq!(loaded.moves(), game.moves());
+        assert_eq!(loaded.result(), GameResult::BlackWins);
+        assert_eq!(loaded.termination(), None);
+        assert_eq!(loaded.board().to_fen(), game.board().to_fen());
+    }
+
+    #[test]
+    fn test_pgn_with_an_unplayable_move() {
+        let mut pgn = parse_pgn("1. f3 e5 2. g4 Qh4# 0-1").unwrap().remove(0);
+        pgn.moves[2].mv = pgn.moves[0].mv;
+
+        let error = Game::from_pgn(&pgn).unwrap_err();
+        assert_eq!(
+            error,
+            PgnError::InvalidMove {
+                move_number: 2,
+                san: "g4".to_string(),
+                error: MoveError::NoPieceAtSource,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "move 2 (g4): illegal move: no piece at the source square"
+        );
+    }
+
+    #[test]
+    fn test_pgn_with_a_move_after_mate() {
+        let mut pgn = parse_pgn("1. f3 e5 2. g4 Qh4# 0-1").unwrap().remove(0);
+        let extra = pgn.moves[0].clone();
+        pgn.moves.push(extra);
+
+        let error = Game::from_pgn(&pgn).unwrap_err();
+        assert_eq!(
+            error,
+            PgnError::MoveAfterGameOver {
+                move_number: 3,
+                san: "f3".to_string(),
+            }
+        );
+        assert_eq!(error.to_string(), "move 3 (f3): the game is already over");
+    }
+}
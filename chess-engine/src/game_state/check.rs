@@ -1,199 +1,73 @@
-use crate::bitboard::operations::square_to_bitboard;
+use crate::bitboard::attacks::{
+    bishop_attacks, king_attacks, knight_attacks, pawn_attacks, rook_attacks,
+};
+use crate::bitboard::{Bitboard, Square};
 use crate::board::Board;
-use crate::pieces::piece_type::Color;
+use crate::pieces::piece_type::{Color, PieceType};
 
 pub fn is_in_check(board: &Board, color: Color) -> bool {
-    let king_position = find_king_position(board, color);
-    if king_position == 64 {
-        return false;
+    match find_king_position(board, color) {
+        Some(king_position) => is_square_attacked(board, king_position, color.opposite()),
+        None => false,
     }
-
-    check_pawn_attack(board, king_position, color)
-        || check_knight_attack(board, king_position, color)
-        || check_bishop_attack(board, king_position, color)
-        || check_rook_attack(board, king_position, color)
 }
 
-pub fn find_king_position(board: &Board, color: Color) -> usize {
-    let king_bitboard = match color {
-        Color::White => board.white_kings,
-        Color::Black => board.black_kings,
-    };
-
-    for i in 0..64 {
-        if king_bitboard & (1u64 << i) != 0 {
-            return i;
-        }
-    }
-
-    64
+pub fn is_square_attacked(board: &Board, square: Square, by_color: Color) -> bool {
+    is_square_attacked_with_occupancy(board, square, by_color, board.all_pieces())
 }
 
-fn check_pawn_attack(board: &Board, king_pos: usize, king_color: Color) -> bool {
-    let king_file = king_pos % 8;
-    let king_rank = king_pos / 8;
-
-    match king_color {
-        Color::White => {
-            let attack_pos = [
-                if king_file > 0 && king_rank < 7 {
-                    Some(king_pos + 7)
-                } else {
-                    None
-                },
-                if king_file < 7 && king_rank < 7 {
-                    Some(king_pos + 9)
-                } else {
-                    None
-                },
-            ];
-
-            for pos in attack_pos.iter().flatten() {
-                let pos_bb = square_to_bitboard(*pos);
-                if board.black_pawns & pos_bb != 0 {
-                    return true;
-                }
-            }
-        }
-        Color::Black => {
-            let attack_pos = [
-                if king_file > 0 && king_rank > 0 {
-                    Some(king_pos - 9)
-                } else {
-                    None
-                },
-                if king_file < 7 && king_rank > 0 {
-                    Some(king_pos - 7)
-                } else {
-                    None
-                },
-            ];
-
-            for pos in attack_pos.iter().flatten() {
-                let pos_bb = square_to_bitboard(*pos);
-                if board.white_pawns & pos_bb != 0 {
-                    return true;
-                }
-            }
-        }
-    }
-
-    false
+pub fn is_square_attacked_with_occupancy(
+    board: &Board,
+    square: Square,
+    by_color: Color,
+    occupancy: Bitboard,
+) -> bool {
+    let square = square.index();
+    let occupancy = occupancy.0;
+
+    check_pawn_attack(board, square, by_color)
+        || check_knight_attack(board, square, by_color)
+        || check_bishop_attack(board, square, by_color, occupancy)
+        || check_rook_attack(board, square, by_color, occupancy)
+        || check_king_attack(board, square, by_color)
 }
 
-fn check_knight_attack(board: &Board, king_pos: usize, king_color: Color) -> bool {
-    let king_file = king_pos % 8;
-    let king_rank = king_pos / 8;
-
-    let offsets = [
-        (-2, -1),
-        (-2, 1),
-        (-1, -2),
-        (-1, 2),
-        (1, -2),
-        (1, 2),
-        (2, -1),
-        (2, 1),
-    ];
-
-    let enemy_knights = match king_color {
-        Color::White => board.black_knights,
-        Color::Black => board.white_knights,
-    };
-
-    for (file_offset, rank_offset) in offsets.iter() {
-        let new_file = king_file as i32 + file_offset;
-        let new_rank = king_rank as i32 + rank_offset;
-
-        if (0..8).contains(&new_file) && (0..8).contains(&new_rank) {
-            let pos = (new_rank as usize) * 8 + (new_file as usize);
-            let pos_bb = square_to_bitboard(pos);
-
-            if enemy_knights & pos_bb != 0 {
-                return true;
-            }
-        }
-    }
-
-    false
+pub fn find_king_position(board: &Board, color: Color) -> Option<Square> {
+    board.pieces(PieceType::King, color).lsb()
 }
 
-fn check_bishop_attack(board: &Board, king_pos: usize, king_color: Color) -> bool {
-    let king_file = king_pos % 8;
-    let king_rank = king_pos / 8;
-
-    let enemy_bishops = match king_color {
-        Color::White => board.black_bishops,
-        Color::Black => board.white_bishops,
-    };
-
-    let enemy_queens = match king_color {
-        Color::White => board.black_queens,
-        Color::Black => board.white_queens,
-    };
+fn check_pawn_attack(board: &Board, square: usize, by_color: Color) -> bool {
+    // A pawn of `by_color` hits `square` exactly when a pawn of the other
+    // color standing on `square` would hit it back.
+    let attackers = board.pieces(PieceType::Pawn, by_color).0;
 
-    let directions = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
-
-    for &(file_dir, rank_dir) in &directions {
-        let mut curr_file = king_file as i32 + file_dir;
-        let mut curr_rank = king_rank as i32 + rank_dir;
-
-        while (0..8).contains(&curr_file) && (0..8).contains(&curr_rank) {
-            let pos = (curr_rank as usize) * 8 + (curr_file as usize);
-            let pos_bb = square_to_bitboard(pos);
-
-            if board.all_pieces() & pos_bb != 0 {
-                if (enemy_bishops | enemy_queens) & pos_bb != 0 {
-                    return true;
-                }
-                break;
-            }
-
-            curr_file += file_dir;
-            curr_rank += rank_dir;
-        }
-    }
-
-    false
+    pawn_attacks(by_color.opposite(), square) & attackers != 0
 }
 
-fn check_rook_attack(board: &Board, king_pos: usize, king_color: Color) -> bool {
-    let king_file = king_pos % 8;
-    let king_rank = king_pos / 8;
+fn check_knight_attack(board: &Board, square: usize, by_color: Color) -> bool {
+    let knights = board.pieces(PieceType::Knight, by_color).0;
 
-    let enemy_rooks = match king_color {
-        Color::White => board.black_rooks,
-        Color::Black => board.white_rooks,
-    };
+    knight_attacks(square) & knights != 0
+}
 
-    let enemy_queens = match king_color {
-        Color::White => board.black_queens,
-        Color::Black => board.white_queens,
-    };
+fn check_king_attack(board: &Board, square: usize, by_color: Color) -> bool {
+    let kings = board.pieces(PieceType::King, by_color).0;
 
-    let directions = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+    king_attacks(square) & kings != 0
+}
 
-    for &(file_dir, rank_dir) in &directions {
-        let mut curr_file = king_file as i32 + file_dir;
-        let mut curr_rank = king_rank as i32 + rank_dir;
+fn check_bishop_attack(board: &Board, square: usize, by_color: Color, occupancy: u64) -> bool {
+    let attackers =
+        (board.pieces(PieceType::Bishop, by_color) | board.pieces(PieceType::Queen, by_color)).0;
 
-        while (0..8).contains(&curr_file) && (0..8).contains(&curr_rank) {
-            let pos = (curr_rank as usize) * 8 + (curr_file as usize);
-            let pos_bb = square_to_bitboard(pos);
+    bishop_attacks(square, occupancy) & attackers != 0
+}
 
-            if board.all_pieces() & pos_bb != 0 {
-                if (enemy_rooks | enemy_queens) & pos_bb != 0 {
-                    return true;
-                }
-                break;
-            }
+fn check_rook_attack(board: &Board, square: usize, by_color: Color, occupancy: u64) -> bool {
+    let attackers =
+        (board.pieces(PieceType::Rook, by_color) | board.pieces(PieceType::Queen, by_color)).0;
 
-            curr_file += file_dir;
-            curr_rank += rank_dir;
-        }
-    }
-
-    false
+    rook_attacks(square, occupancy) & attackers != 0
 }
 
 #[cfg(test)]
@@ -205,10 +79,10 @@ mod tests {
         let board = Board::new();
 
         let white_king_pos = find_king_position(&board, Color::White);
-        assert_eq!(white_king_pos, 4); // e1
+        assert_eq!(white_king_pos, Some(Square::E1));
 
         let black_king_pos = find_king_position(&board, Color::Black);
-        assert_eq!(black_king_pos, 60); // e8
+        assert_eq!(black_king_pos, Some(Square::E8));
     }
 
     #[test]
@@ -221,66 +95,115 @@ mod tests {
 
     #[test]
     fn test_is_in_check_by_queen() {
-        let mut board = Board::new();
-
-        board.white_kings = 1u64 << 4; // e1
-        board.black_queens = 1u64 << 12; // e2
-        board.black_pawns = 0;
+        let board = Board::from_fen("rnb1kbnr/8/8/8/8/8/PPPPqPPP/RNBQKBNR w KQkq - 0 1").unwrap();
 
         assert!(is_in_check(&board, Color::White));
     }
 
     #[test]
     fn test_is_in_check_by_rook() {
-        let mut board = Board::new();
-
-        board.white_kings = 1u64 << 4; // e1
-        board.black_rooks = 1u64 << 36; // e5
-        board.black_pawns = 0;
-        board.white_pawns = 0;
+        let board = Board::from_fen("1nbqkbn1/8/8/4r3/8/8/8/RNBQKBNR w KQ - 0 1").unwrap();
 
         assert!(is_in_check(&board, Color::White));
     }
 
     #[test]
     fn test_is_not_in_check_with_blocked_path() {
-        let mut board = Board::new();
-
-        board.white_kings = 1u64 << 4; // e1
-        board.black_rooks = 1u64 << 36; // e5
-        board.white_pawns = 1u64 << 12; // e2
+        let board = Board::from_fen("1nbqkbn1/pppppppp/8/4r3/8/8/4P3/RNBQKBNR w KQ - 0 1").unwrap();
 
         assert!(!is_in_check(&board, Color::White));
     }
 
     #[test]
     fn test_is_in_check_by_knight() {
-        let mut board = Board::new();
-
-        board.white_kings = 1u64 << 4; // e1
-        board.black_knights = 1u64 << 14; // g2
+        let board =
+            Board::from_fen("r1bqkb1r/pppppppp/8/8/8/8/PPPPPPnP/RNBQKBNR w KQkq - 0 1").unwrap();
 
         assert!(is_in_check(&board, Color::White));
     }
 
     #[test]
     fn test_is_in_check_by_bishop() {
-        let mut board = Board::new();
-
-        board.white_kings = 1u64 << 4; // e1
-        board.black_bishops = 1u64 << 31; // h4
-        board.white_pawns = 0;
+        let board = Board::from_fen("rn1qk1nr/pppppppp/8/8/7b/8/8/RNBQKBNR w KQkq - 0 1").unwrap();
 
         assert!(is_in_check(&board, Color::White));
     }
 
     #[test]
     fn test_is_in_check_by_pawn() {
-        let mut board = Board::new();
+        let board = Board::from_fen("rnbqkbnr/8/8/5p2/4K3/8/PPPPPPPP/RNBQ1BNR w kq - 0 1").unwrap();
+
+        assert!(is_in_check(&board, Color::White));
+    }
 
-        board.white_kings = 1u64 << 28; // e4
-        board.black_pawns = 1u64 << 37; // f5
+    #[test]
+    fn test_is_in_check_by_king() {
+        let board = Board::from_fen_unchecked("8/8/8/3k4/4K3/8/8/8 w - - 0 1").unwrap();
 
         assert!(is_in_check(&board, Color::White));
+        assert!(is_in_check(&board, Color::Black));
+    }
+
+    #[test]
+    fn test_is_not_in_check_by_distant_king() {
+        let board = Board::from_fen("8/8/3k4/8/4K3/8/8/8 w - - 0 1").unwrap();
+
+        assert!(!is_in_check(&board, Color::White));
+    }
+
+    #[test]
+    fn test_square_attacked_by_each_piece_type() {
+        let cases = [
+            ("4k3/8/8/8/3p4/8/8/4K3 w - - 0 1", Square::E3, Color::Black), // pawn d4 hits e3
+            ("4k3/8/8/8/8/3P4/8/4K3 w - - 0 1", Square::E4, Color::White), // pawn d3 hits e4
+            ("4k3/8/8/8/8/5n2/8/4K3 w - - 0 1", Square::G1, Color::Black), // knight f3 hits g1
+            ("4k3/8/8/8/8/8/8/B3K3 w - - 0 1", Square::H8, Color::White),  // bishop a1 hits h8
+            ("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", Square::A8, Color::White),  // rook a1 hits a8
+            ("4k3/8/8/8/8/8/8/Q3K3 w - - 0 1", Square::E5, Color::White),  // queen a1 hits e5
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 1", Square::F2, Color::White),   // king e1 hits f2
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 1", Square::D7, Color::Black),   // king e8 hits d7
+        ];
+
+        for (fen, square, by_color) in cases {
+            let board = Board::from_fen(fen).unwrap();
+            assert!(is_square_attacked(&board, square, by_color), "{}", fen);
+            assert!(
+                !is_square_attacked(&board, square, by_color.opposite()),
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn test_pawns_only_attack_forward() {
+        let board = Board::from_fen("4k3/8/8/8/3P4/8/8/4K3 w - - 0 1").unwrap();
+
+        assert!(is_square_attacked(&board, Square::E5, Color::White));
+        assert!(!is_square_attacked(&board, Square::E3, Color::White));
+        assert!(!is_square_attacked(&board, Square::D5, Color::White));
+    }
+
+    #[test]
+    fn test_slider_attack_blocked() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/RN2K3 w - - 0 1").unwrap();
+
+        assert!(!is_square_attacked(&board, Square::C1, Color::White)); // behind the knight
+        assert!(is_square_attacked(&board, Square::B1, Color::White)); // defended
+        assert!(is_square_attacked(&board, Square::A2, Color::White));
+    }
+
+    #[test]
+    fn test_attack_with_custom_occupancy() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/r2K4 w - - 0 1").unwrap();
+        let without_king = board.all_pieces() & !board.pieces(PieceType::King, Color::White);
+
+        assert!(!is_square_attacked(&board, Square::E1, Color::Black));
+        assert!(is_square_attacked_with_occupancy(
+            &board,
+            Square::E1,
+            Color::Black,
+            without_king
+        ));
     }
 }
@@ -1,5 +1,12 @@
 pub mod bitboard;
 pub mod board;
+pub mod chess960;
+pub mod eval;
+pub mod game;
 pub mod game_state;
 pub mod movement;
+pub mod perft;
+pub mod pgn;
 pub mod pieces;
+pub mod search;
+pub mod zobrist;
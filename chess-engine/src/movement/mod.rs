@@ -1 +1,3 @@
+pub mod moves;
+pub mod san;
 pub mod validator;
@@ -0,0 +1,204 @@
+use crate::bitboard::Square;
+use crate::pieces::piece_type::PieceType;
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign, Deref};
+
+pub const MAX_MOVES: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MoveFlags(u8);
+
+impl MoveFlags {
+    pub const QUIET: Self = Self(0);
+    pub const CAPTURE: Self = Self(1);
+    pub const EN_PASSANT: Self = Self(1 << 1);
+    pub const CASTLING: Self = Self(1 << 2);
+    pub const DOUBLE_PUSH: Self = Self(1 << 3);
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for MoveFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for MoveFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move {
+    from: u8,
+    to: u8,
+    promotion: Option<PieceType>,
+    flags: MoveFlags,
+}
+
+impl Move {
+    pub fn new(from: Square, to: Square, promotion: Option<PieceType>, flags: MoveFlags) -> Self {
+        Self {
+            from: from.index() as u8,
+            to: to.index() as u8,
+            promotion,
+            flags,
+        }
+    }
+
+    pub fn from(&self) -> Square {
+        Square::new(self.from as usize)
+    }
+
+    pub fn to(&self) -> Square {
+        Square::new(self.to as usize)
+    }
+
+    pub fn promotion(&self) -> Option<PieceType> {
+        self.promotion
+    }
+
+    pub fn flags(&self) -> MoveFlags {
+        self.flags
+    }
+
+    pub fn is_capture(&self) -> bool {
+        self.flags.contains(MoveFlags::CAPTURE)
+    }
+
+    pub fn is_en_passant(&self) -> bool {
+        self.flags.contains(MoveFlags::EN_PASSANT)
+    }
+
+    pub fn is_castling(&self) -> bool {
+        self.flags.contains(MoveFlags::CASTLING)
+    }
+
+    pub fn is_double_push(&self) -> bool {
+        self.flags.contains(MoveFlags::DOUBLE_PUSH)
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.from(), self.to())?;
+
+        match self.promotion {
+            Some(PieceType::Queen) => write!(f, "q"),
+            Some(PieceType::Rook) => write!(f, "r"),
+            Some(PieceType::Bishop) => write!(f, "b"),
+            Some(PieceType::Knight) => write!(f, "n"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    pub fn new() -> Self {
+        Self {
+            moves: [Move::new(Square::A1, Square::A1, None, MoveFlags::QUIET); MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, mv: Move) {
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_accessors() {
+        let mv = Move::new(
+            Square::E7,
+            Square::F6,
+            None,
+            MoveFlags::CAPTURE | MoveFlags::EN_PASSANT,
+        );
+
+        assert_eq!(mv.from(), Square::E7);
+        assert_eq!(mv.to(), Square::F6);
+        assert_eq!(mv.promotion(), None);
+        assert!(mv.is_capture());
+        assert!(mv.is_en_passant());
+        assert!(!mv.is_castling());
+        assert!(!mv.is_double_push());
+    }
+
+    #[test]
+    fn test_move_display() {
+        let quiet = Move::new(Square::E2, Square::E4, None, MoveFlags::DOUBLE_PUSH);
+        let promotion = Move::new(
+            Square::E7,
+            Square::E8,
+            Some(PieceType::Knight),
+            MoveFlags::QUIET,
+        );
+
+        assert_eq!(quiet.to_string(), "e2e4");
+        assert_eq!(promotion.to_string(), "e7e8n");
+    }
+
+    #[test]
+    fn test_move_list() {
+        let mut list = MoveList::new();
+        assert!(list.is_empty());
+
+        list.push(Move::new(
+            Square::E2,
+            Square::E4,
+            None,
+            MoveFlags::DOUBLE_PUSH,
+        ));
+        list.push(Move::new(Square::G1, Square::F3, None, MoveFlags::QUIET));
+
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[1].to(), Square::F3);
+        assert_eq!(list.iter().filter(|mv| mv.is_double_push()).count(), 1);
+    }
+}
@@ -0,0 +1,343 @@
+use crate::bitboard::Square;
+use crate::board::Board;
+use crate::game_state::{check, game_status};
+use crate::movement::moves::Move;
+use crate::pieces::piece_type::{Color, Piece, PieceType};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SanError {
+    InvalidSyntax(String),
+    IllegalMove(String),
+    AmbiguousMove { san: String, candidates: Vec<Move> },
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SanError::InvalidSyntax(san) => write!(f, "'{}' is not a valid SAN move", san),
+            SanError::IllegalMove(san) => write!(f, "'{}' is not a legal move here", san),
+            SanError::AmbiguousMove { san, candidates } => {
+                write!(f, "'{}' is ambiguous, it could be", san)?;
+                for (i, mv) in candidates.iter().enumerate() {
+                    write!(f, "{} {}", if i > 0 { "," } else { "" }, mv)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SanError {}
+
+pub fn move_to_san(board: &Board, mv: Move) -> String {
+    let mut san = String::new();
+
+    let piece_type = match board.piece_at(mv.from()) {
+        Some(piece) => piece.piece_type,
+        None => return mv.to_string(),
+    };
+
+    if mv.is_castling() {
+        san.push_str(if mv.to().file() > mv.from().file() {
+            "O-O"
+        } else {
+            "O-O-O"
+        });
+    } else if piece_type == PieceType::Pawn {
+        if mv.is_capture() {
+            san.push(file_char(mv.from()));
+            san.push('x');
+        }
+        san.push_str(&mv.to().to_string());
+        if let Some(promotion) = mv.promotion() {
+            san.push('=');
+            san.push(piece_letter(promotion));
+        }
+    } else {
+        san.push(piece_letter(piece_type));
+        san.push_str(&disambiguation(board, mv, piece_type));
+        if mv.is_capture() {
+            san.push('x');
+        }
+        san.push_str(&mv.to().to_string());
+    }
+
+    let mut after = board.clone();
+    after.make_move_unchecked(mv);
+    let opponent = after.side_to_move;
+    if game_status::is_checkmate(&after, opponent) {
+        san.push('#');
+    } else if check::is_in_check(&after, opponent) {
+        san.push('+');
+    }
+
+    san
+}
+
+pub fn parse_san(board: &Board, san: &str) -> Result<Move, SanError> {
+    let trimmed = san.trim_end_matches(['+', '#', '!', '?']);
+    let invalid = || SanError::InvalidSyntax(san.to_string());
+
+    let moves = board.generate_moves();
+
+    let castling_side = match trimmed {
+        "O-O" | "0-0" => Some(true),
+        "O-O-O" | "0-0-0" => Some(false),
+        _ => None,
+    };
+    if let Some(kingside) = castling_side {
+        return moves
+            .iter()
+            .copied()
+            .find(|mv| mv.is_castling() && (mv.to().file() > mv.from().file()) == kingside)
+            .ok_or_else(|| SanError::IllegalMove(san.to_string()));
+    }
+
+    let (body, promotion) = match trimmed.char_indices().rev().nth(1) {
+        Some((i, '=')) => (&trimmed[..i], Some(&trimmed[i + 1..])),
+        _ if trimmed.ends_with(['N', 'B', 'R', 'Q']) && trimmed.len() > 2 => {
+            let i = trimmed.len() - 1;
+            (&trimmed[..i], Some(&trimmed[i..]))
+        }
+        _ => (trimmed, None),
+    };
+    let promotion = match promotion {
+        Some(symbol) => match Piece::from_fen_symbol(symbol.chars().next().ok_or_else(invalid)?) {
+            Some(piece)
+                if symbol.len() == 1
+                    && !matches!(piece.piece_type, PieceType::Pawn | PieceType::King) =>
+            {
+                Some(piece.piece_type)
+            }
+            _ => return Err(invalid()),
+        },
+        None => None,
+    };
+
+    let (piece_type, body) = match body.chars().next() {
+        Some(symbol @ ('N' | 'B' | 'R' | 'Q' | 'K')) => (
+            Piece::from_fen_symbol(symbol)
+                .ok_or_else(invalid)?
+                .piece_type,
+            &body[1..],
+        ),
+        Some(_) => (PieceType::Pawn, body),
+        None => return Err(invalid()),
+    };
+
+    if body.len() < 2 || !body.is_ascii() {
+        return Err(invalid());
+    }
+    let to = Square::from_algebraic(&body[body.len() - 2..]).ok_or_else(invalid)?;
+    let qualifier = body[..body.len() - 2].trim_end_matches('x');
+
+    let mut from_file = None;
+    let mut from_rank = None;
+    for symbol in qualifier.chars() {
+        match symbol {
+            'a'..='h' if from_file.is_none() && from_rank.is_none() => {
+                from_file = Some(symbol as usize - 'a' as usize)
+            }
+            '1'..='8' if from_rank.is_none() => from_rank = Some(symbol as usize - '1' as usize),
+            _ => return Err(invalid()),
+        }
+    }
+
+    let candidates: Vec<Move> = moves
+        .iter()
+        .copied()
+        .filter(|mv| {
+            mv.to() == to
+                && mv.promotion() == promotion
+                && !mv.is_castling()
+                && board.piece_at(mv.from()).map(|piece| piece.piece_type) == Some(piece_type)
+                && from_file.is_none_or(|file| mv.from().file() == file)
+                && from_rank.is_none_or(|rank| mv.from().rank() == rank)
+        })
+        .collect();
+
+    match candidates.as_slice() {
+        [] => Err(SanError::IllegalMove(san.to_string())),
+        [mv] => Ok(*mv),
+        _ => Err(SanError::AmbiguousMove {
+            san: san.to_string(),
+            candidates,
+        }),
+    }
+}
+
+fn disambiguation(board: &Board, mv: Move, piece_type: PieceType) -> String {
+    let rivals: Vec<Square> = board
+        .generate_moves()
+        .iter()
+        .filter(|other| {
+            other.to() == mv.to()
+                && other.from() != mv.from()
+                && board.piece_at(other.from()).map(|piece| piece.piece_type) == Some(piece_type)
+        })
+        .map(|other| other.from())
+        .collect();
+
+    let from = mv.from();
+    if rivals.is_empty() {
+        String::new()
+    } else if rivals.iter().all(|rival| rival.file() != from.file()) {
+        file_char(from).to_string()
+    } else if rivals.iter().all(|rival| rival.rank() != from.rank()) {
+        (from.rank() + 1).to_string()
+    } else {
+        from.to_string()
+    }
+}
+
+fn file_char(square: Square) -> char {
+    (b'a' + square.file() as u8) as char
+}
+
+fn piece_letter(piece_type: PieceType) -> char {
+    Piece::new(piece_type, Color::White).fen_symbol()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn san_after(fen: &str, from: Square, to: Square, promotion: Option<PieceType>) -> String {
+        let board = Board::from_fen(fen).unwrap();
+        let mv = board
+            .generate_moves()
+            .iter()
+            .copied()
+            .find(|mv| mv.from() == from && mv.to() == to && mv.promotion() == promotion)
+            .unwrap();
+
+        move_to_san(&board, mv)
+    }
+
+    #[test]
+    fn test_basic_moves() {
+        let board = Board::new();
+        let mv = parse_san(&board, "Nf3").unwrap();
+
+        assert_eq!((mv.from(), mv.to()), (Square::G1, Square::F3));
+        assert_eq!(move_to_san(&board, mv), "Nf3");
+        assert_eq!(parse_san(&board, "e4").unwrap().to(), Square::E4);
+        assert_eq!(
+            san_after(
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+                Square::E4,
+                Square::D5,
+                None
+            ),
+            "exd5"
+        );
+    }
+
+    #[test]
+    fn test_castling() {
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        let board = Board::from_fen(fen).unwrap();
+
+        assert_eq!(san_after(fen, Square::E1, Square::G1, None), "O-O");
+        assert_eq!(san_after(fen, Square::E1, Square::C1, None), "O-O-O");
+        assert_eq!(parse_san(&board, "O-O-O").unwrap().to(), Square::C1);
+        assert_eq!(parse_san(&board, "0-0").unwrap().to(), Square::G1);
+    }
+
+    #[test]
+    fn test_promotion_and_check_suffixes() {
+        let fen = "k7/4P3/8/8/8/8/8/4K3 w - - 0 1";
+        let board = Board::from_fen(fen).unwrap();
+
+        assert_eq!(
+            san_after(fen, Square::E7, Square::E8, Some(PieceType::Queen)),
+            "e8=Q+"
+        );
+        assert_eq!(
+            san_after(fen, Square::E7, Square::E8, Some(PieceType::Knight)),
+            "e8=N"
+        );
+        assert_eq!(
+            parse_san(&board, "e8=Q+").unwrap().promotion(),
+            Some(PieceType::Queen)
+        );
+        assert_eq!(
+            parse_san(&board, "e8R").unwrap().promotion(),
+            Some(PieceType::Rook)
+        );
+        assert_eq!(
+            parse_san(&board, "e8"),
+            Err(SanError::IllegalMove("e8".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_disambiguation_and_mate() {
+        let fen = "2rkr3/2p1p3/8/4N3/8/8/8/R4R1K w - - 0 1";
+        let board = Board::from_fen(fen).unwrap();
+
+        assert_eq!(san_after(fen, Square::A1, Square::D1, None), "Rad1#");
+        assert_eq!(san_after(fen, Square::F1, Square::D1, None), "Rfd1#");
+        assert_eq!(parse_san(&board, "Rad1#").unwrap().from(), Square::A1);
+
+        let fen = "8/8/6k1/8/8/Q7/8/Q1Q4K w - - 0 1";
+        assert_eq!(san_after(fen, Square::A1, Square::B2, None), "Qa1b2");
+        assert_eq!(san_after(fen, Square::A3, Square::B2, None), "Q3b2");
+        assert_eq!(san_after(fen, Square::C1, Square::B2, None), "Qcb2");
+    }
+
+    #[test]
+    fn test_ambiguous_and_illegal_moves() {
+        let board = Board::from_fen("8/8/6k1/8/8/Q7/8/Q1Q4K w - - 0 1").unwrap();
+
+        match parse_san(&board, "Qb2") {
+            Err(SanError::AmbiguousMove { candidates, .. }) => assert_eq!(candidates.len(), 3),
+            other => panic!("expected an ambiguous move, got {:?}", other),
+        }
+        assert_eq!(
+            parse_san(&board, "Qab2"),
+            Err(SanError::AmbiguousMove {
+                san: "Qab2".to_string(),
+                candidates: vec![
+                    parse_san(&board, "Qa1b2").unwrap(),
+                    parse_san(&board, "Q3b2").unwrap(),
+                ],
+            })
+        );
+        assert_eq!(
+            parse_san(&board, "Kh3"),
+            Err(SanError::IllegalMove("Kh3".to_string()))
+        );
+        assert_eq!(
+            parse_san(&board, "Zz9"),
+            Err(SanError::InvalidSyntax("Zz9".to_string()))
+        );
+        assert_eq!(
+            SanError::IllegalMove("Kh3".to_string()).to_string(),
+            "'Kh3' is not a legal move here"
+        );
+    }
+
+    #[test]
+    fn test_en_passant() {
+        let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        let mv = parse_san(&board, "exd6").unwrap();
+
+        assert!(mv.is_en_passant());
+        assert_eq!(move_to_san(&board, mv), "exd6");
+    }
+
+    #[test]
+    fn test_round_trip_every_legal_move() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        let moves = board.generate_moves();
+
+        for &mv in &moves {
+            let san = move_to_san(&board, mv);
+            assert_eq!(parse_san(&board, &san), Ok(mv), "{}", san);
+        }
+    }
+}
@@ -1,19 +1,16 @@
-use crate::bitboard::operations::square_to_bitboard;
-use crate::pieces::piece_type::{Color, MoveError};
+use crate::bitboard::{Bitboard, Square};
+use crate::pieces::piece_type::{Color, MoveError, PieceType};
 
 pub fn validate_move(
-    from: usize,
-    to: usize,
+    from: Square,
+    to: Square,
     side_to_move: Color,
-    white_pieces: u64,
-    black_pieces: u64,
-    legal_moves: u64,
+    white_pieces: Bitboard,
+    black_pieces: Bitboard,
+    legal_moves: Bitboard,
 ) -> Result<(), MoveError> {
-    let from_bb = square_to_bitboard(from);
-    let to_bb = square_to_bitboard(to);
-
-    let is_white_piece = white_pieces & from_bb != 0;
-    let is_black_piece = black_pieces & from_bb != 0;
+    let is_white_piece = white_pieces.contains(from);
+    let is_black_piece = black_pieces.contains(from);
 
     if !is_white_piece && !is_black_piece {
         return Err(MoveError::NoPieceAtSource);
@@ -26,17 +23,35 @@ pub fn validate_move(
     }
 
     let is_destination_occupied_by_same_color = match side_to_move {
-        Color::White => white_pieces & to_bb != 0,
-        Color::Black => black_pieces & to_bb != 0,
+        Color::White => white_pieces.contains(to),
+        Color::Black => black_pieces.contains(to),
     };
 
-    if is_destination_occupied_by_same_color {
+    // Chess960 castling is encoded as the king moving onto its own rook.
+    if is_destination_occupied_by_same_color && !legal_moves.contains(to) {
         return Err(MoveError::DestinationOccupiedBySameColor);
     }
 
-    if legal_moves & to_bb == 0 {
+    if !legal_moves.contains(to) {
         return Err(MoveError::InvalidDestination);
     }
 
     Ok(())
 }
+
+pub fn validate_promotion(
+    piece_type: PieceType,
+    to: Square,
+    promotion: Option<PieceType>,
+) -> Result<(), MoveError> {
+    let reaches_last_rank = piece_type == PieceType::Pawn && (to.rank() == 0 || to.rank() == 7);
+
+    match (reaches_last_rank, promotion) {
+        (false, None) => Ok(()),
+        (
+            true,
+            Some(PieceType::Queen | PieceType::Rook | PieceType::Bishop | PieceType::Knight),
+        ) => Ok(()),
+        _ => Err(MoveError::InvalidPromotion),
+    }
+}
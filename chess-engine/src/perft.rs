@@ -0,0 +1,174 @@
+use crate::board::Board;
+use crate::movement::moves::Move;
+
+pub fn perft(board: &mut Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = board.generate_moves();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let mut nodes = 0;
+    for &mv in &moves {
+        board.make_move_unchecked(mv);
+        nodes += perft(board, depth - 1);
+        board.unmake_move();
+    }
+
+    nodes
+}
+
+pub fn divide(board: &mut Board, depth: u32) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+
+    let moves = board.generate_moves();
+    let mut results = Vec::with_capacity(moves.len());
+
+    for &mv in &moves {
+        board.make_move_unchecked(mv);
+        results.push((mv, perft(board, depth - 1)));
+        board.unmake_move();
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::STARTING_FEN;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+    const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+    const POSITION_4_MIRRORED: &str =
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1";
+    const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+    const POSITION_6: &str =
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+    fn assert_perft(fen: &str, expected: &[u64]) {
+        let mut board = Board::from_fen(fen).unwrap();
+
+        for (depth, &nodes) in expected.iter().enumerate() {
+            let depth = depth as u32 + 1;
+            assert_eq!(
+                perft(&mut board, depth),
+                nodes,
+                "{} at depth {}",
+                fen,
+                depth
+            );
+            let after = if board.chess960 {
+                board.to_shredder_fen()
+            } else {
+                board.to_fen()
+            };
+            assert_eq!(after, fen);
+        }
+    }
+
+    #[test]
+    fn test_perft_depth_zero() {
+        assert_eq!(perft(&mut Board::new(), 0), 1);
+    }
+
+    #[test]
+    fn test_perft_starting_position() {
+        assert_perft(STARTING_FEN, &[20, 400, 8902, 197281]);
+    }
+
+    #[test]
+    fn test_perft_kiwipete() {
+        assert_perft(KIWIPETE, &[48, 2039, 97862]);
+    }
+
+    #[test]
+    fn test_perft_position_3() {
+        assert_perft(POSITION_3, &[14, 191, 2812, 43238]);
+    }
+
+    #[test]
+    fn test_perft_position_4() {
+        assert_perft(POSITION_4, &[6, 264, 9467]);
+        assert_perft(POSITION_4_MIRRORED, &[6, 264, 9467]);
+    }
+
+    #[test]
+    fn test_perft_position_5() {
+        assert_perft(POSITION_5, &[44, 1486, 62379]);
+    }
+
+    #[test]
+    fn test_perft_position_6() {
+        assert_perft(POSITION_6, &[46, 2079, 89890]);
+    }
+
+    #[test]
+    fn test_perft_chess960() {
+        assert_perft(
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+            &[21, 528, 12189],
+        );
+        assert_perft(
+            "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+            &[21, 807, 18002],
+        );
+        assert_perft(
+            "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+            &[20, 479, 10471],
+        );
+        assert_perft(
+            "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9",
+            &[22, 593, 13440],
+        );
+        assert_perft(
+            "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9",
+            &[28, 1120, 31058],
+        );
+    }
+
+    #[test]
+    #[ignore = "slow in debug builds, run with --release --ignored"]
+    fn test_perft_deep() {
+        let cases = [
+            (STARTING_FEN, 5, 4865609),
+            (KIWIPETE, 4, 4085603),
+            (POSITION_3, 5, 674624),
+            (POSITION_4, 4, 422333),
+            (POSITION_5, 4, 2103487),
+            (POSITION_6, 4, 3894594),
+        ];
+
+        for (fen, depth, nodes) in cases {
+            let mut board = Board::from_fen(fen).unwrap();
+            assert_eq!(
+                perft(&mut board, depth),
+                nodes,
+                "{} at depth {}",
+                fen,
+                depth
+            );
+        }
+    }
+
+    #[test]
+    fn test_divide_sums_to_perft() {
+        let mut board = Board::from_fen(KIWIPETE).unwrap();
+        let results = divide(&mut board, 2);
+
+        assert_eq!(results.len(), 48);
+        assert_eq!(results.iter().map(|(_, nodes)| nodes).sum::<u64>(), 2039);
+
+        let castle = results
+            .iter()
+            .find(|(mv, _)| mv.to_string() == "e1g1")
+            .unwrap();
+        assert_eq!(castle.1, 43);
+    }
+}
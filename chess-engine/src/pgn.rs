@@ -0,0 +1,813 @@
+use crate::board::{Board, FenError, STARTING_FEN};
+use crate::game::GameResult;
+use crate::game_state::game_status::{get_game_status, GameStatus};
+use crate::movement::moves::Move;
+use crate::movement::san::{move_to_san, parse_san, SanError};
+use crate::pieces::piece_type::{Color, MoveError};
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+const MAX_LINE_LENGTH: usize = 80;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnError {
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    InvalidFen(FenError),
+    IllegalMove {
+        move_number: u32,
+        error: SanError,
+    },
+    // A parsed move that the board rejected when it was replayed.
+    InvalidMove {
+        move_number: u32,
+        san: String,
+        error: MoveError,
+    },
+    // A move recorded after the game had already ended, e.g. past a mate.
+    MoveAfterGameOver {
+        move_number: u32,
+        san: String,
+    },
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnError::Syntax { line, message } => {
+                write!(f, "PGN syntax error on line {}: {}", line, message)
+            }
+            PgnError::InvalidFen(error) => write!(f, "invalid FEN tag: {}", error),
+            PgnError::IllegalMove { move_number, error } => {
+                write!(f, "move {}: {}", move_number, error)
+            }
+            PgnError::InvalidMove {
+                move_number,
+                san,
+                error,
+            } => write!(f, "move {} ({}): illegal move: {}", move_number, san, error),
+            PgnError::MoveAfterGameOver { move_number, san } => {
+                write!(
+                    f,
+                    "move {} ({}): the game is already over",
+                    move_number, san
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+// A move in the game tree. `variations` holds alternatives to this move, each
+// starting from the position before it was played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnMove {
+    pub mv: Move,
+    pub san: String,
+    pub nags: Vec<u8>,
+    pub comment_before: Option<String>,
+    pub comment: Option<String>,
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+impl PgnMove {
+    fn new(mv: Move, san: String) -> Self {
+        Self {
+            mv,
+            san,
+            nags: Vec::new(),
+            comment_before: None,
+            comment: None,
+            variations: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<PgnMove>,
+    pub result: GameResult,
+}
+
+impl PgnGame {
+    pub fn new() -> Self {
+        Self {
+            tags: SEVEN_TAG_ROSTER
+                .iter()
+                .map(|&(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            moves: Vec::new(),
+            result: GameResult::Ongoing,
+        }
+    }
+
+    pub fn from_board(board: &Board) -> Self {
+        let mut start = board.clone();
+        let mut played = Vec::new();
+        while let Some(mv) = start.unmake_move() {
+            played.push(mv);
+        }
+
+        let mut game = Self::new();
+        let fen = start.to_fen();
+        if start.chess960 {
+            game.set_tag("Variant", "Chess960");
+        }
+        if fen != STARTING_FEN || start.chess960 {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &fen);
+        }
+
+        for &mv in played.iter().rev() {
+            game.moves.push(PgnMove::new(mv, move_to_san(&start, mv)));
+            start.make_move_unchecked(mv);
+        }
+
+        game.result = match get_game_status(board) {
+            GameStatus::Checkmate => GameResult::win_for(board.side_to_move.opposite()),
+            GameStatus::Stalemate | GameStatus::Draw(_) => GameResult::Draw,
+            GameStatus::Ongoing | GameStatus::Check => GameResult::Ongoing,
+        };
+        game.set_tag("Result", game.result.as_pgn());
+
+        game
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, existing)) => *existing = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn starting_board(&self) -> Result<Board, PgnError> {
+        let mut board = match self.tag("FEN") {
+            Some(fen) => Board::from_fen(fen).map_err(PgnError::InvalidFen)?,
+            None => Board::new(),
+        };
+        if self
+            .tag("Variant")
+            .is_some_and(|variant| variant.eq_ignore_ascii_case("chess960"))
+        {
+            board.chess960 = true;
+        }
+        Ok(board)
+    }
+
+    // The position at the end of the main line.
+    pub fn board(&self) -> Result<Board, PgnError> {
+        let mut board = self.starting_board()?;
+
+        for node in &self.moves {
+            let move_number = board.fullmove_number;
+            board
+                .play_move(node.mv)
+                .map_err(|error| PgnError::InvalidMove {
+                    move_number,
+                    san: node.san.clone(),
+                    error,
+                })?;
+        }
+
+        Ok(board)
+    }
+}
+
+impl Default for PgnGame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for PgnGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let result = self.result.as_pgn();
+
+        for &(name, default) in &SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Result" => result,
+                _ => self.tag(name).unwrap_or(default),
+            };
+            writeln!(f, "[{} \"{}\"]", name, escape_tag_value(value))?;
+        }
+        for (name, value) in &self.tags {
+            if !SEVEN_TAG_ROSTER.iter().any(|&(roster, _)| roster == name) {
+                writeln!(f, "[{} \"{}\"]", name, escape_tag_value(value))?;
+            }
+        }
+        writeln!(f)?;
+
+        let start = self.starting_board().unwrap_or_default();
+        let mut tokens = Vec::new();
+        write_line(
+            &mut tokens,
+            &self.moves,
+            start.fullmove_number,
+            start.side_to_move,
+        );
+        tokens.push(result.to_string());
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + 1 + token.len() > MAX_LINE_LENGTH {
+                writeln!(f)?;
+                line_length = 0;
+            }
+            if line_length > 0 {
+                write!(f, " ")?;
+                line_length += 1;
+            }
+            write!(f, "{}", token)?;
+            line_length += token.len();
+        }
+        writeln!(f)
+    }
+}
+
+fn write_line(tokens: &mut Vec<String>, line: &[PgnMove], mut number: u32, mut color: Color) {
+    let mut needs_number = true;
+
+    for node in line {
+        if let Some(comment) = &node.comment_before {
+            push_comment(tokens, comment);
+            needs_number = true;
+        }
+
+        match color {
+            Color::White => tokens.push(format!("{}.", number)),
+            Color::Black if needs_number => tokens.push(format!("{}...", number)),
+            Color::Black => {}
+        }
+        tokens.push(node.san.clone());
+        tokens.extend(node.nags.iter().map(|nag| format!("${}", nag)));
+        needs_number = false;
+
+        if let Some(comment) = &node.comment {
+            push_comment(tokens, comment);
+            needs_number = true;
+        }
+
+        for variation in &node.variations {
+            let start = tokens.len();
+            write_line(tokens, variation, number, color);
+            if tokens.len() > start {
+                tokens[start].insert(0, '(');
+                tokens.last_mut().unwrap().push(')');
+                needs_number = true;
+            }
+        }
+
+        if color == Color::Black {
+            number += 1;
+        }
+        color = color.opposite();
+    }
+}
+
+fn push_comment(tokens: &mut Vec<String>, comment: &str) {
+    let start = tokens.len();
+    tokens.extend(comment.split_whitespace().map(str::to_string));
+    if tokens.len() == start {
+        tokens.push("{}".to_string());
+    } else {
+        tokens[start].insert(0, '{');
+        tokens.last_mut().unwrap().push('}');
+    }
+}
+
+fn escape_tag_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    OpenVariation,
+    CloseVariation,
+    Result(GameResult),
+    San(String),
+}
+
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>, PgnError> {
+    let mut tokens = tokenize(text)?.into_iter().peekable();
+    let mut games = Vec::new();
+
+    while tokens.peek().is_some() {
+        let mut game = PgnGame {
+            tags: Vec::new(),
+            moves: Vec::new(),
+            result: GameResult::Ongoing,
+        };
+
+        while let Some((_, Token::Tag(name, value))) = tokens.peek() {
+            game.set_tag(name, value);
+            tokens.next();
+        }
+
+        if let Some(result) = game.tag("Result").and_then(GameResult::from_pgn) {
+            game.result = result;
+        }
+
+        let mut board = game.starting_board()?;
+        game.moves = parse_line(&mut tokens, &mut board, 0)?;
+
+        if let Some((_, Token::Result(result))) = tokens.peek() {
+            game.result = *result;
+            game.set_tag("Result", result.as_pgn());
+            tokens.next();
+        }
+
+        // Comments after the result still belong to the game that just ended.
+        while let Some((_, Token::Comment(_))) = tokens.peek() {
+            if let Some((_, Token::Comment(text))) = tokens.next() {
+                if let Some(last) = game.moves.last_mut() {
+                    append_comment(&mut last.comment, text);
+                }
+            }
+        }
+
+        if game.tags.is_empty() && game.moves.is_empty() {
+            continue;
+        }
+        games.push(game);
+    }
+
+    Ok(games)
+}
+
+type Tokens = Peekable<std::vec::IntoIter<(usize, Token)>>;
+
+fn parse_line(
+    tokens: &mut Tokens,
+    board: &mut Board,
+    depth: usize,
+) -> Result<Vec<PgnMove>, PgnError> {
+    let mut line: Vec<PgnMove> = Vec::new();
+    let mut comment_before: Option<String> = None;
+
+    loop {
+        let line_number = match tokens.peek() {
+            Some((_, Token::CloseVariation)) if depth > 0 => break,
+            Some((_, Token::Tag(..) | Token::Result(_))) | None => break,
+            Some((line_number, _)) => *line_number,
+        };
+        let unexpected = |message: &str| PgnError::Syntax {
+            line: line_number,
+            message: message.to_string(),
+        };
+
+        match tokens.next().map(|(_, token)| token) {
+            Some(Token::San(san)) => {
+                let move_number = board.fullmove_number;
+                let mv = parse_san(board, &san)
+                    .map_err(|error| PgnError::IllegalMove { move_number, error })?;
+
+                let mut node = PgnMove::new(mv, move_to_san(board, mv));
+                node.comment_before = comment_before.take();
+                board.make_move_unchecked(mv);
+                line.push(node);
+            }
+            Some(Token::Comment(text)) => {
+                let comment = match line.last_mut() {
+                    Some(last) => &mut last.comment,
+                    None => &mut comment_before,
+                };
+                append_comment(comment, text);
+            }
+            Some(Token::Nag(nag)) => match line.last_mut() {
+                Some(last) => last.nags.push(nag),
+                None => return Err(unexpected("annotation before any move")),
+            },
+            Some(Token::OpenVariation) => {
+                let last = line
+                    .last_mut()
+                    .ok_or_else(|| unexpected("variation before any move"))?;
+
+                let mut variation_board = board.clone();
+                variation_board.unmake_move();
+                let variation = parse_line(tokens, &mut variation_board, depth + 1)?;
+                match tokens.next() {
+                    Some((_, Token::CloseVariation)) => last.variations.push(variation),
+                    _ => return Err(unexpected("unterminated variation")),
+                }
+            }
+            Some(Token::CloseVariation) => return Err(unexpected("unmatched ')'")),
+            Some(Token::Tag(..) | Token::Result(_)) | None => unreachable!(),
+        }
+    }
+
+    Ok(line)
+}
+
+fn append_comment(comment: &mut Option<String>, text: String) {
+    match comment {
+        Some(existing) => {
+            existing.push(' ');
+            existing.push_str(&text);
+        }
+        None => *comment = Some(text),
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    let mut at_line_start = true;
+
+    while let Some(c) = chars.next() {
+        let token_line = line;
+        let syntax_error = |message: &str| PgnError::Syntax {
+            line: token_line,
+            message: message.to_string(),
+        };
+
+        if c == '\n' {
+            line += 1;
+            at_line_start = true;
+            continue;
+        }
+        if c == '%' && at_line_start {
+            skip_to_line_end(&mut chars);
+            continue;
+        }
+        at_line_start = false;
+
+        match c {
+            _ if c.is_whitespace() => {}
+            '[' => {
+                let content = take_until(&mut chars, ']', &mut line)
+                    .ok_or_else(|| syntax_error("unterminated tag"))?;
+                let (name, value) =
+                    parse_tag(&content).ok_or_else(|| syntax_error("malformed tag"))?;
+                tokens.push((token_line, Token::Tag(name, value)));
+            }
+            '{' => {
+                let content = take_until(&mut chars, '}', &mut line)
+                    .ok_or_else(|| syntax_error("unterminated comment"))?;
+                let comment = content.split_whitespace().collect::<Vec<_>>().join(" ");
+                tokens.push((token_line, Token::Comment(comment)));
+            }
+            ';' => {
+                let comment = skip_to_line_end(&mut chars);
+                tokens.push((token_line, Token::Comment(comment.trim().to_string())));
+            }
+            '(' => tokens.push((token_line, Token::OpenVariation)),
+            ')' => tokens.push((token_line, Token::CloseVariation)),
+            '*' => tokens.push((token_line, Token::Result(GameResult::Ongoing))),
+            '$' => {
+                let digits = take_while(&mut chars, |c| c.is_ascii_digit());
+                let nag = digits
+                    .parse()
+                    .map_err(|_| syntax_error("malformed annotation glyph"))?;
+                tokens.push((token_line, Token::Nag(nag)));
+            }
+            _ => {
+                let mut symbol = c.to_string();
+                symbol.push_str(&take_while(&mut chars, |c| {
+                    !c.is_whitespace() && !"[]{}();$*".contains(c)
+                }));
+                push_symbol(&mut tokens, token_line, &symbol);
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn push_symbol(tokens: &mut Vec<(usize, Token)>, line: usize, symbol: &str) {
+    if let Some(result) = GameResult::from_pgn(symbol) {
+        tokens.push((line, Token::Result(result)));
+        return;
+    }
+
+    // Move numbers may be glued to the move that follows them, as in "12.Nf3".
+    let symbol = match symbol.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) if i > 0 && symbol[i..].starts_with('.') => symbol[i..].trim_start_matches('.'),
+        None => "",
+        _ => symbol,
+    };
+    if symbol.is_empty() || symbol.chars().all(|c| c == '.') {
+        return;
+    }
+
+    let san = symbol.trim_end_matches(['!', '?']);
+    let nag = match &symbol[san.len()..] {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    };
+
+    tokens.push((line, Token::San(san.to_string())));
+    if let Some(nag) = nag {
+        tokens.push((line, Token::Nag(nag)));
+    }
+}
+
+fn parse_tag(content: &str) -> Option<(String, String)> {
+    let content = content.trim();
+    let name_end = content.find(char::is_whitespace)?;
+    let name = &content[..name_end];
+    let quoted = content[name_end..].trim();
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+
+    let mut value = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?),
+            _ => value.push(c),
+        }
+    }
+
+    Some((name.to_string(), value))
+}
+
+fn take_until(chars: &mut Peekable<Chars>, end: char, line: &mut usize) -> Option<String> {
+    let mut content = String::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for c in chars.by_ref() {
+        if c == '\n' {
+            *line += 1;
+        }
+        if c == end && !in_quotes {
+            return Some(content);
+        }
+        // Tag values are quoted and may contain ']'; comments have no quoting.
+        if end == ']' {
+            if c == '"' && !escaped {
+                in_quotes = !in_quotes;
+            }
+            escaped = c == '\\' && !escaped;
+        }
+        content.push(c);
+    }
+
+    None
+}
+
+fn take_while(chars: &mut Peekable<Chars>, predicate: impl Fn(char) -> bool) -> String {
+    let mut taken = String::new();
+    while let Some(&c) = chars.peek() {
+        if !predicate(c) {
+            break;
+        }
+        taken.push(c);
+        chars.next();
+    }
+    taken
+}
+
+fn skip_to_line_end(chars: &mut Peekable<Chars>) -> String {
+    take_while(chars, |c| c != '\n')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::Square;
+
+    const SAMPLE: &str = r#"[Event "Casual Game"]
+[Site "Berlin GER"]
+[Date "1852.??.??"]
+[Round "?"]
+[White "Adolf Anderssen"]
+[Black "Jean Dufresne"]
+[Result "1-0"]
+
+1.e4 e5 2.Nf3 Nc6 3.Bc4 Bc5 4.b4 {Evans Gambit} Bxb4 5.c3 Ba5 6.d4 exd4 7.O-O
+d3 8.Qb3 Qf6 9.e5 Qg6 10.Re1 Nge7 11.Ba3 b5 $2 (11...O-O 12.Nbd2) 12.Qxb5 Rb8
+13.Qa4 Bb6 14.Nbd2 Bb7 15.Ne4 Qf5 16.Bxd3 Qh5 17.Nf6+ gxf6 18.exf6 Rg8 19.Rad1!
+Qxf3 20.Rxe7+ Nxe7 21.Qxd7+ Kxd7 22.Bf5+ Ke8 23.Bd7+ Kf8 24.Bxe7# 1-0
+
+[Event "Second"]
+[White "A"]
+[Black "B"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 b - - 0 1"]
+[SetUp "1"]
+
+1... Kd7 ; the king heads for the queenside
+2. e4 (2. e3 Kc6 (2... Ke6)) 2... Kc6 *
+"#;
+
+    #[test]
+    fn test_parse_multiple_games() {
+        let games = parse_pgn(SAMPLE).unwrap();
+        assert_eq!(games.len(), 2);
+
+        let evergreen = &games[0];
+        assert_eq!(evergreen.tag("White"), Some("Adolf Anderssen"));
+        assert_eq!(evergreen.result, GameResult::WhiteWins);
+        assert_eq!(evergreen.moves.len(), 47);
+        assert_eq!(evergreen.moves[6].comment.as_deref(), Some("Evans Gambit"));
+        assert_eq!(evergreen.moves[21].san, "b5");
+        assert_eq!(evergreen.moves[21].nags, vec![2]);
+        assert_eq!(evergreen.moves[21].variations[0][0].san, "O-O");
+        assert_eq!(evergreen.moves[36].nags, vec![1]);
+        assert_eq!(evergreen.moves[46].san, "Bxe7#");
+
+        let board = evergreen.board().unwrap();
+        assert_eq!(get_game_status(&board), GameStatus::Checkmate);
+    }
+
+    #[test]
+    fn test_parse_from_fen_with_nested_variations() {
+        let games = parse_pgn(SAMPLE).unwrap();
+        let game = &games[1];
+
+        assert_eq!(game.result, GameResult::Ongoing);
+        assert_eq!(game.tag("Result"), Some("*"));
+        assert_eq!(
+            game.moves[0].comment.as_deref(),
+            Some("the king heads for the queenside")
+        );
+
+        let variation = &game.moves[1].variations[0];
+        assert_eq!(variation[0].san, "e3");
+        assert_eq!(variation[1].san, "Kc6");
+        assert_eq!(variation[1].variations[0][0].san, "Ke6");
+
+        let board = game.board().unwrap();
+        assert_eq!(board.to_fen(), "8/8/2k5/8/4P3/8/8/4K3 w - - 1 3");
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let games = parse_pgn(SAMPLE).unwrap();
+
+        for game in &games {
+            let exported = game.to_string();
+            let reparsed = parse_pgn(&exported).unwrap();
+            assert_eq!(reparsed.len(), 1);
+            assert_eq!(&reparsed[0].moves, &game.moves);
+            assert_eq!(reparsed[0].result, game.result);
+            assert!(exported.lines().all(|line| line.len() <= MAX_LINE_LENGTH));
+        }
+
+        let exported = games[1].to_string().replace('\n', " ");
+        assert!(exported.contains(
+            "1... Kd7 {the king heads for the queenside} 2. e4 (2. e3 Kc6 (2... Ke6)) 2... Kc6 *"
+        ));
+    }
+
+    #[test]
+    fn test_export_played_game_with_seven_tag_roster() {
+        let mut board = Board::new();
+        for (from, to) in [
+            (Square::F2, Square::F3),
+            (Square::E7, Square::E5),
+            (Square::G2, Square::G4),
+            (Square::D8, Square::H4),
+        ] {
+            board.make_move(from, to).unwrap();
+        }
+
+        let game = PgnGame::from_board(&board);
+        let exported = game.to_string();
+
+        assert_eq!(
+            exported,
+            "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n\
+             [White \"?\"]\n[Black \"?\"]\n[Result \"0-1\"]\n\n\
+             1. f3 e5 2. g4 Qh4# 0-1\n"
+        );
+        assert_eq!(
+            parse_pgn(&exported).unwrap()[0].board().unwrap().to_fen(),
+            board.to_fen()
+        );
+    }
+
+    #[test]
+    fn test_export_from_custom_position() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1").unwrap();
+        board.make_move(Square::E8, Square::D7).unwrap();
+
+        let exported = PgnGame::from_board(&board).to_string();
+
+        assert!(exported.contains("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 1\"]"));
+        assert!(exported.ends_with("1... Kd7 *\n"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_pgn("[Event \"x\"]\n\n1. e4 e5 2. Ke3 *"),
+            Err(PgnError::IllegalMove {
+                move_number: 2,
+                error: SanError::IllegalMove("Ke3".to_string())
+            })
+        );
+        assert_eq!(
+            parse_pgn("1. e4 {unterminated"),
+            Err(PgnError::Syntax {
+                line: 1,
+                message: "unterminated comment".to_string()
+            })
+        );
+        assert_eq!(
+            parse_pgn("1. e4\n(1. d4 *"),
+            Err(PgnError::Syntax {
+                line: 2,
+                message: "unterminated variation".to_string()
+            })
+        );
+        assert!(matches!(
+            parse_pgn("[FEN \"8/8/8/8/8/8/8/8 w - - 0 1\"]\n*"),
+            Err(PgnError::InvalidFen(_))
+        ));
+    }
+
+    #[test]
+    fn test_comments_after_the_result() {
+        let games = parse_pgn("1. e4 e5 1-0 {done}\n").unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].result, GameResult::WhiteWins);
+        assert_eq!(games[0].moves[1].comment.as_deref(), Some("done"));
+
+        let games =
+            parse_pgn("[Event \"a\"]\n\n1. d4 * ; end\n\n[Event \"b\"]\n\n1. c4 *\n").unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].moves[0].comment.as_deref(), Some("end"));
+        assert_eq!(games[1].tag("Event"), Some("b"));
+
+        assert_eq!(parse_pgn("  {nothing to see}\n"), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_unplayable_moves_keep_their_error() {
+        let mut game = parse_pgn("1. e4 e5 *").unwrap().remove(0);
+        // Swap in a move that is not legal after 1. e4.
+        game.moves[1].mv = game.moves[0].mv;
+
+        assert_eq!(
+            game.board(),
+            Err(PgnError::InvalidMove {
+                move_number: 1,
+                san: "e5".to_string(),
+                error: MoveError::NoPieceAtSource,
+            })
+        );
+        assert_eq!(
+            game.board().unwrap_err().to_string(),
+            "move 1 (e5): illegal move: no piece at the source square"
+        );
+    }
+
+    #[test]
+    fn test_tag_escapes() {
+        let games = parse_pgn("[Event \"The \\\"Immortal\\\" [game]\"]\n*").unwrap();
+
+        assert_eq!(games[0].tag("Event"), Some("The \"Immortal\" [game]"));
+        assert!(games[0]
+            .to_string()
+            .starts_with("[Event \"The \\\"Immortal\\\" [game]\"]\n"));
+    }
+
+    #[test]
+    fn test_chess960_variant_round_trip() {
+        let mut board =
+            crate::chess960::start_position(crate::chess960::STANDARD_POSITION).unwrap();
+        for san in ["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5", "O-O"] {
+            board.play_move(parse_san(&board, san).unwrap()).unwrap();
+        }
+
+        let game = PgnGame::from_board(&board);
+        assert_eq!(game.tag("Variant"), Some("Chess960"));
+        assert_eq!(game.tag("FEN"), Some(STARTING_FEN));
+        assert_eq!(game.moves[6].mv.to(), Square::H1);
+
+        let parsed = parse_pgn(&game.to_string()).unwrap();
+        assert_eq!(parsed[0].moves[6].mv, game.moves[6].mv);
+        assert_eq!(parsed[0].board().unwrap().to_fen(), board.to_fen());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::bitboard::attacks::bishop_attacks;
 use crate::bitboard::operations::square_to_bitboard;
 use crate::pieces::piece_type::Color;
 
@@ -14,10 +15,6 @@ pub fn get_bishop_moves(
         Color::White => white_pieces,
         Color::Black => black_pieces,
     };
-    let opponent_pieces = match side_to_move {
-        Color::White => black_pieces,
-        Color::Black => white_pieces,
-    };
 
     let is_bishop_at_square = match side_to_move {
         Color::White => white_bishops & from_bb != 0,
@@ -28,41 +25,7 @@ pub fn get_bishop_moves(
         return 0;
     }
 
-    let mut moves = 0u64;
-
-    let directions = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
-
-    let file = from % 8;
-    let rank = from / 8;
-
-    for &(file_delta, rank_delta) in &directions {
-        let mut curr_file = file as i32;
-        let mut curr_rank = rank as i32;
-
-        loop {
-            curr_file += file_delta;
-            curr_rank += rank_delta;
-
-            if !(0..=7).contains(&curr_file) || !(0..=7).contains(&curr_rank) {
-                break;
-            }
-
-            let target = (curr_rank as usize) * 8 + (curr_file as usize);
-            let target_bb = square_to_bitboard(target);
-
-            if target_bb & own_pieces != 0 {
-                break;
-            }
-
-            moves |= target_bb;
-
-            if target_bb & opponent_pieces != 0 {
-                break;
-            }
-        }
-    }
-
-    moves
+    bishop_attacks(from, white_pieces | black_pieces) & !own_pieces
 }
 
 #[cfg(test)]
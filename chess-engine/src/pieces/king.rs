@@ -1,3 +1,4 @@
+use crate::bitboard::attacks::king_attacks;
 use crate::bitboard::operations::square_to_bitboard;
 use crate::pieces::piece_type::Color;
 
@@ -14,10 +15,6 @@ pub fn get_king_moves(
         Color::White => white_pieces,
         Color::Black => black_pieces,
     };
-    let _opponent_pieces = match side_to_move {
-        Color::White => black_pieces,
-        Color::Black => white_pieces,
-    };
 
     let is_king_at_square = match side_to_move {
         Color::White => white_kings & from_bb != 0,
@@ -28,38 +25,53 @@ pub fn get_king_moves(
         return 0;
     }
 
+    king_attacks(from) & !own_pieces
+}
+
+// Castling always lands the king on the c- or g-file and the rook next to it on
+// the d- or f-file, wherever they started (Chess960). Returns king destinations.
+pub fn get_castling_moves(
+    from: usize,
+    kingside_rook: Option<usize>,
+    queenside_rook: Option<usize>,
+    own_rooks: u64,
+    all_pieces: u64,
+    is_attacked: impl Fn(usize) -> bool,
+) -> u64 {
+    if is_attacked(from) {
+        return 0;
+    }
+
+    let back_rank = from - from % 8;
     let mut moves = 0u64;
 
-    let directions = [
-        (0, 1),
-        (1, 1),
-        (1, 0),
-        (1, -1),
-        (0, -1),
-        (-1, -1),
-        (-1, 0),
-        (-1, 1),
-    ];
-
-    let file = from % 8;
-    let rank = from / 8;
-
-    for &(file_delta, rank_delta) in &directions {
-        let new_file = file as i32 + file_delta;
-        let new_rank = rank as i32 + rank_delta;
-
-        if !(0..=7).contains(&new_file) || !(0..=7).contains(&new_rank) {
+    for (rook, king_file, rook_file) in [(kingside_rook, 6, 5), (queenside_rook, 2, 3)] {
+        let rook = match rook {
+            Some(rook) if own_rooks & square_to_bitboard(rook) != 0 => rook,
+            _ => continue,
+        };
+        let king_to = back_rank + king_file;
+        let rook_to = back_rank + rook_file;
+
+        let low = from.min(rook).min(king_to).min(rook_to);
+        let high = from.max(rook).max(king_to).max(rook_to);
+        let must_be_empty = (low..=high)
+            .filter(|&square| square != from && square != rook)
+            .fold(0u64, |bb, square| bb | square_to_bitboard(square));
+        if all_pieces & must_be_empty != 0 {
             continue;
         }
 
-        let target = (new_rank as usize) * 8 + (new_file as usize);
-        let target_bb = square_to_bitboard(target);
-
-        if target_bb & own_pieces != 0 {
+        let king_path = if king_to > from {
+            from + 1..=king_to
+        } else {
+            king_to..=from - 1
+        };
+        if king_path.into_iter().any(&is_attacked) {
             continue;
         }
 
-        moves |= target_bb;
+        moves |= square_to_bitboard(king_to);
     }
 
     moves
@@ -137,4 +149,61 @@ mod tests {
 
         assert_eq!(moves, expected);
     }
+
+    #[test]
+    fn test_castling_both_sides() {
+        let white_rooks = (1u64 << 0) | (1u64 << 7);
+        let all_pieces = white_rooks | (1u64 << 4);
+
+        let moves = get_castling_moves(4, Some(7), Some(0), white_rooks, all_pieces, |_| false);
+
+        assert_eq!(moves, (1u64 << 2) | (1u64 << 6));
+    }
+
+    #[test]
+    fn test_castling_without_rights() {
+        let black_rooks = (1u64 << 56) | (1u64 << 63);
+        let all_pieces = black_rooks | (1u64 << 60);
+
+        let moves = get_castling_moves(60, None, Some(56), black_rooks, all_pieces, |_| false);
+
+        assert_eq!(moves, 1u64 << 58);
+    }
+
+    #[test]
+    fn test_castling_through_attacked_square() {
+        let white_rooks = (1u64 << 0) | (1u64 << 7);
+        let all_pieces = white_rooks | (1u64 << 4);
+
+        let moves = get_castling_moves(4, Some(7), Some(0), white_rooks, all_pieces, |sq| sq == 3);
+
+        assert_eq!(moves, 1u64 << 6);
+    }
+
+    #[test]
+    fn test_chess960_castling_paths() {
+        // King on b1 with rooks on a1 and g1, as in BRKxxxxR-style setups.
+        let white_rooks = (1u64 << 0) | (1u64 << 6);
+        let all_pieces = white_rooks | (1u64 << 1);
+
+        let moves = get_castling_moves(1, Some(6), Some(0), white_rooks, all_pieces, |_| false);
+        assert_eq!(moves, (1u64 << 2) | (1u64 << 6));
+
+        // With the king on f1, a piece on b1 is only in the queenside rook's way.
+        let white_rooks = (1u64 << 0) | (1u64 << 7);
+        let all_pieces = white_rooks | (1u64 << 5) | (1u64 << 1);
+        let moves = get_castling_moves(5, Some(7), Some(0), white_rooks, all_pieces, |_| false);
+        assert_eq!(moves, 1u64 << 6);
+
+        // A king already on g1 stays put, so an attacked f1 only matters to the rook.
+        let king_on_g1 = get_castling_moves(
+            6,
+            Some(7),
+            None,
+            1u64 << 7,
+            (1u64 << 7) | (1u64 << 6),
+            |sq| sq == 5,
+        );
+        assert_eq!(king_on_g1, 1u64 << 6);
+    }
 }
@@ -1,3 +1,4 @@
+use crate::bitboard::attacks::knight_attacks;
 use crate::bitboard::operations::square_to_bitboard;
 use crate::pieces::piece_type::Color;
 
@@ -24,62 +25,7 @@ pub fn get_knight_moves(
         return 0;
     }
 
-    let mut moves = 0u64;
-
-    let file = from % 8;
-    let rank = from / 8;
-
-    let possible_moves = [
-        if file > 1 && rank < 7 {
-            Some(from + 6)
-        } else {
-            None
-        },
-        if file > 0 && rank < 6 {
-            Some(from + 15)
-        } else {
-            None
-        },
-        if file < 7 && rank < 6 {
-            Some(from + 17)
-        } else {
-            None
-        },
-        if file < 6 && rank < 7 {
-            Some(from + 10)
-        } else {
-            None
-        },
-        if file < 6 && rank > 0 {
-            Some(from - 6)
-        } else {
-            None
-        },
-        if file < 7 && rank > 1 {
-            Some(from - 15)
-        } else {
-            None
-        },
-        if file > 0 && rank > 1 {
-            Some(from - 17)
-        } else {
-            None
-        },
-        if file > 1 && rank > 0 {
-            Some(from - 10)
-        } else {
-            None
-        },
-    ];
-
-    for target in possible_moves.iter().flatten() {
-        let target_bb = square_to_bitboard(*target);
-        if target_bb & own_pieces == 0 {
-            moves |= target_bb;
-        }
-    }
-
-    moves
+    knight_attacks(from) & !own_pieces
 }
 
 #[cfg(test)]
@@ -1,9 +1,19 @@
+use crate::bitboard::attacks::pawn_attacks;
 use crate::bitboard::operations::square_to_bitboard;
 use crate::pieces::piece_type::Color;
 
-pub fn get_pawn_moves(from: usize, white_pawns: u64, black_pawns: u64, side_to_move: Color) -> u64 {
+pub fn get_pawn_moves(
+    from: usize,
+    white_pawns: u64,
+    black_pawns: u64,
+    white_pieces: u64,
+    black_pieces: u64,
+    en_passant_square: Option<usize>,
+    side_to_move: Color,
+) -> u64 {
     let from_bb = square_to_bitboard(from);
-    let occupied = white_pawns | black_pawns;
+    let occupied = white_pieces | black_pieces;
+    let en_passant_bb = en_passant_square.map_or(0, square_to_bitboard);
     let mut moves = 0u64;
 
     match side_to_move {
@@ -24,18 +34,7 @@ pub fn get_pawn_moves(from: usize, white_pawns: u64, black_pawns: u64, side_to_m
                 }
             }
 
-            if from % 8 != 0 {
-                let capture_left = from_bb << 7;
-                if capture_left & black_pawns != 0 {
-                    moves |= capture_left;
-                }
-            }
-            if from % 8 != 7 {
-                let capture_right = from_bb << 9;
-                if capture_right & black_pawns != 0 {
-                    moves |= capture_right;
-                }
-            }
+            moves |= pawn_attacks(Color::White, from) & (black_pieces | en_passant_bb);
         }
         Color::Black => {
             if black_pawns & from_bb == 0 {
@@ -54,18 +53,7 @@ pub fn get_pawn_moves(from: usize, white_pawns: u64, black_pawns: u64, side_to_m
                 }
             }
 
-            if from % 8 != 7 {
-                let capture_right = from_bb >> 7;
-                if capture_right & white_pawns != 0 {
-                    moves |= capture_right;
-                }
-            }
-            if from % 8 != 0 {
-                let capture_left = from_bb >> 9;
-                if capture_left & white_pawns != 0 {
-                    moves |= capture_left;
-                }
-            }
+            moves |= pawn_attacks(Color::Black, from) & (white_pieces | en_passant_bb);
         }
     }
 
@@ -80,7 +68,15 @@ mod tests {
     fn test_white_pawn_single_push() {
         let white_pawns = 1u64 << 8;
         let black_pawns = 0;
-        let moves = get_pawn_moves(8, white_pawns, black_pawns, Color::White);
+        let moves = get_pawn_moves(
+            8,
+            white_pawns,
+            black_pawns,
+            white_pawns,
+            black_pawns,
+            None,
+            Color::White,
+        );
 
         let expected = (1u64 << 16) | (1u64 << 24);
         assert_eq!(moves, expected);
@@ -90,7 +86,15 @@ mod tests {
     fn test_white_pawn_double_push() {
         let white_pawns = 1u64 << 8;
         let black_pawns = 0;
-        let moves = get_pawn_moves(8, white_pawns, black_pawns, Color::White);
+        let moves = get_pawn_moves(
+            8,
+            white_pawns,
+            black_pawns,
+            white_pawns,
+            black_pawns,
+            None,
+            Color::White,
+        );
 
         let expected = (1u64 << 16) | (1u64 << 24);
         assert_eq!(moves, expected);
@@ -100,7 +104,15 @@ mod tests {
     fn test_white_pawn_capture() {
         let white_pawns = 1u64 << 8;
         let black_pawns = 1u64 << 17;
-        let moves = get_pawn_moves(8, white_pawns, black_pawns, Color::White);
+        let moves = get_pawn_moves(
+            8,
+            white_pawns,
+            black_pawns,
+            white_pawns,
+            black_pawns,
+            None,
+            Color::White,
+        );
 
         let expected = (1u64 << 16) | (1u64 << 24) | (1u64 << 17);
         assert_eq!(moves, expected);
@@ -110,7 +122,15 @@ mod tests {
     fn test_black_pawn_single_push() {
         let white_pawns = 0;
         let black_pawns = 1u64 << 50;
-        let moves = get_pawn_moves(50, white_pawns, black_pawns, Color::Black);
+        let moves = get_pawn_moves(
+            50,
+            white_pawns,
+            black_pawns,
+            white_pawns,
+            black_pawns,
+            None,
+            Color::Black,
+        );
 
         let expected = (1u64 << 42) | (1u64 << 34);
         assert_eq!(moves, expected);
@@ -120,7 +140,15 @@ mod tests {
     fn test_black_pawn_double_push() {
         let white_pawns = 0;
         let black_pawns = 1u64 << 50;
-        let moves = get_pawn_moves(50, white_pawns, black_pawns, Color::Black);
+        let moves = get_pawn_moves(
+            50,
+            white_pawns,
+            black_pawns,
+            white_pawns,
+            black_pawns,
+            None,
+            Color::Black,
+        );
 
         let expected = (1u64 << 42) | (1u64 << 34);
         assert_eq!(moves, expected);
@@ -130,9 +158,155 @@ mod tests {
     fn test_black_pawn_capture() {
         let white_pawns = 1u64 << 43;
         let black_pawns = 1u64 << 50;
-        let moves = get_pawn_moves(50, white_pawns, black_pawns, Color::Black);
+        let moves = get_pawn_moves(
+            50,
+            white_pawns,
+            black_pawns,
+            white_pawns,
+            black_pawns,
+            None,
+            Color::Black,
+        );
 
         let expected = (1u64 << 42) | (1u64 << 34) | (1u64 << 43);
         assert_eq!(moves, expected);
     }
+
+    #[test]
+    fn test_white_pawn_en_passant() {
+        let white_pawns = 1u64 << 36;
+        let black_pawns = 1u64 << 35;
+        let moves = get_pawn_moves(
+            36,
+            white_pawns,
+            black_pawns,
+            white_pawns,
+            black_pawns,
+            Some(43),
+            Color::White,
+        );
+
+        let expected = (1u64 << 44) | (1u64 << 43);
+        assert_eq!(moves, expected);
+    }
+
+    #[test]
+    fn test_black_pawn_en_passant() {
+        let white_pawns = 1u64 << 25;
+        let black_pawns = 1u64 << 24;
+        let moves = get_pawn_moves(
+            24,
+            white_pawns,
+            black_pawns,
+            white_pawns,
+            black_pawns,
+            Some(17),
+            Color::Black,
+        );
+
+        let expected = (1u64 << 16) | (1u64 << 17);
+        assert_eq!(moves, expected);
+    }
+
+    #[test]
+    fn test_white_pawn_blocked_by_non_pawns() {
+        let white_pawns = 1u64 << 12;
+        let black_knight = 1u64 << 20;
+
+        let moves = get_pawn_moves(
+            12,
+            white_pawns,
+            0,
+            white_pawns,
+            black_knight,
+            None,
+            Color::White,
+        );
+
+        assert_eq!(moves, 0);
+    }
+
+    #[test]
+    fn test_white_pawn_double_push_blocked_by_own_piece() {
+        let white_pawns = 1u64 << 12;
+        let white_queen = 1u64 << 28;
+
+        let moves = get_pawn_moves(
+            12,
+            white_pawns,
+            0,
+            white_pawns | white_queen,
+            0,
+            None,
+            Color::White,
+        );
+
+        assert_eq!(moves, 1u64 << 20);
+    }
+
+    #[test]
+    fn test_black_pawn_blocked_by_non_pawns() {
+        let black_pawns = 1u64 << 52;
+        let white_bishop = 1u64 << 36;
+
+        let moves = get_pawn_moves(
+            52,
+            0,
+            black_pawns,
+            white_bishop,
+            black_pawns,
+            None,
+            Color::Black,
+        );
+
+        assert_eq!(moves, 1u64 << 44);
+    }
+
+    #[test]
+    fn test_white_pawn_captures_non_pawns() {
+        let white_pawns = 1u64 << 27;
+        let black_pieces = (1u64 << 34) | (1u64 << 36);
+
+        let moves = get_pawn_moves(
+            27,
+            white_pawns,
+            0,
+            white_pawns,
+            black_pieces,
+            None,
+            Color::White,
+        );
+
+        let expected = (1u64 << 35) | (1u64 << 34) | (1u64 << 36);
+        assert_eq!(moves, expected);
+    }
+
+    #[test]
+    fn test_black_pawn_captures_non_pawns() {
+        let black_pawns = 1u64 << 35;
+        let white_pieces = (1u64 << 26) | (1u64 << 28);
+
+        let moves = get_pawn_moves(
+            35,
+            0,
+            black_pawns,
+            white_pieces,
+            black_pawns,
+            None,
+            Color::Black,
+        );
+
+        let expected = (1u64 << 27) | (1u64 << 26) | (1u64 << 28);
+        assert_eq!(moves, expected);
+    }
+
+    #[test]
+    fn test_pawn_does_not_capture_own_pieces() {
+        let white_pawns = 1u64 << 27;
+        let white_pieces = white_pawns | (1u64 << 34) | (1u64 << 36);
+
+        let moves = get_pawn_moves(27, white_pawns, 0, white_pieces, 0, None, Color::White);
+
+        assert_eq!(moves, 1u64 << 35);
+    }
 }
@@ -1,10 +1,30 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Color {
     White,
     Black,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+impl Color {
+    pub fn opposite(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::White => write!(f, "White"),
+            Color::Black => write!(f, "Black"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PieceType {
     Pawn,
     Knight,
@@ -14,11 +34,114 @@ pub enum PieceType {
     Queen,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Piece {
+    pub piece_type: PieceType,
+    pub color: Color,
+}
+
+impl Piece {
+    pub const fn new(piece_type: PieceType, color: Color) -> Self {
+        Self { piece_type, color }
+    }
+
+    pub fn from_fen_symbol(symbol: char) -> Option<Self> {
+        let color = if symbol.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+
+        let piece_type = match symbol.to_ascii_lowercase() {
+            'p' => PieceType::Pawn,
+            'n' => PieceType::Knight,
+            'b' => PieceType::Bishop,
+            'r' => PieceType::Rook,
+            'k' => PieceType::King,
+            'q' => PieceType::Queen,
+            _ => return None,
+        };
+
+        Some(Self::new(piece_type, color))
+    }
+
+    pub fn fen_symbol(self) -> char {
+        let symbol = match self.piece_type {
+            PieceType::Pawn => 'p',
+            PieceType::Knight => 'n',
+            PieceType::Bishop => 'b',
+            PieceType::Rook => 'r',
+            PieceType::King => 'k',
+            PieceType::Queen => 'q',
+        };
+
+        match self.color {
+            Color::White => symbol.to_ascii_uppercase(),
+            Color::Black => symbol,
+        }
+    }
+}
+
+impl From<(PieceType, Color)> for Piece {
+    fn from((piece_type, color): (PieceType, Color)) -> Self {
+        Self::new(piece_type, color)
+    }
+}
+
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.fen_symbol())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MoveError {
     NoPieceAtSource,
     WrongColorPiece,
     InvalidDestination,
     PathBlocked,
     DestinationOccupiedBySameColor,
+    InvalidPromotion,
+    LeavesKingInCheck,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            MoveError::NoPieceAtSource => "no piece at the source square",
+            MoveError::WrongColorPiece => "that's not your piece to move",
+            MoveError::InvalidDestination => "invalid destination for this piece",
+            MoveError::PathBlocked => "the path is blocked by another piece",
+            MoveError::DestinationOccupiedBySameColor => {
+                "destination is occupied by your own piece"
+            }
+            MoveError::InvalidPromotion => "choose a promotion piece (q, r, b or n)",
+            MoveError::LeavesKingInCheck => "that move would leave your king in check",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_piece_fen_symbols() {
+        let white_knight = Piece::new(PieceType::Knight, Color::White);
+
+        assert_eq!(white_knight.fen_symbol(), 'N');
+        assert_eq!(Piece::from_fen_symbol('N'), Some(white_knight));
+        assert_eq!(
+            Piece::from_fen_symbol('q'),
+            Some(Piece::new(PieceType::Queen, Color::Black))
+        );
+        assert_eq!(Piece::from_fen_symbol('x'), None);
+        assert_eq!(
+            Piece::from((PieceType::King, Color::Black)).to_string(),
+            "k"
+        );
+    }
 }
@@ -1,7 +1,6 @@
+use crate::bitboard::attacks::queen_attacks;
 use crate::bitboard::operations::square_to_bitboard;
-use crate::pieces::bishop;
 use crate::pieces::piece_type::Color;
-use crate::pieces::rook;
 
 pub fn get_queen_moves(
     from: usize,
@@ -12,6 +11,10 @@ pub fn get_queen_moves(
     side_to_move: Color,
 ) -> u64 {
     let from_bb = square_to_bitboard(from);
+    let own_pieces = match side_to_move {
+        Color::White => white_pieces,
+        Color::Black => black_pieces,
+    };
 
     let is_queen_at_square = match side_to_move {
         Color::White => white_queens & from_bb != 0,
@@ -22,25 +25,7 @@ pub fn get_queen_moves(
         return 0;
     }
 
-    let bishop_moves = bishop::get_bishop_moves(
-        from,
-        from_bb,
-        from_bb,
-        white_pieces,
-        black_pieces,
-        side_to_move,
-    );
-
-    let rook_moves = rook::get_rook_moves(
-        from,
-        from_bb,
-        from_bb,
-        white_pieces,
-        black_pieces,
-        side_to_move,
-    );
-
-    bishop_moves | rook_moves
+    queen_attacks(from, white_pieces | black_pieces) & !own_pieces
 }
 
 #[cfg(test)]
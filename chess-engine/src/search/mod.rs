@@ -0,0 +1,619 @@
+pub mod ordering;
+pub mod see;
+pub mod tt;
+
+use crate::board::Board;
+use crate::eval;
+use crate::game_state::{check, game_status};
+use crate::movement::moves::Move;
+use ordering::{HistoryTable, KillerMoves, MovePicker};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tt::{Bound, TranspositionTable};
+
+pub const MATE_SCORE: i32 = 30_000;
+pub const MAX_PLY: usize = 64;
+const INFINITY: i32 = MATE_SCORE + 1;
+
+// How often the clock is read; checking it on every node is measurable.
+const TIME_CHECK_INTERVAL: u64 = 1024;
+
+// Any limit left as None is unbounded. With none set the search runs to
+// MAX_PLY, so callers normally set at least one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub time: Option<Duration>,
+}
+
+impl SearchLimits {
+    pub fn depth(depth: u32) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Self::default()
+        }
+    }
+
+    pub fn nodes(nodes: u64) -> Self {
+        Self {
+            nodes: Some(nodes),
+            ..Self::default()
+        }
+    }
+
+    pub fn time(time: Duration) -> Self {
+        Self {
+            time: Some(time),
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    // None only when the side to move has no legal moves.
+    pub best_move: Option<Move>,
+    // Centipawns from the side to move's point of view, or a mate score.
+    pub score: i32,
+    pub depth: u32,
+    pub pv: Vec<Move>,
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+impl SearchResult {
+    pub fn mate_in(&self) -> Option<i32> {
+        mate_in(self.score)
+    }
+}
+
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() >= MATE_SCORE - MAX_PLY as i32
+}
+
+// Full moves until mate: positive when the side to move delivers it,
+// negative when it is the one being mated.
+pub fn mate_in(score: i32) -> Option<i32> {
+    if !is_mate_score(score) {
+        return None;
+    }
+
+    let moves = (MATE_SCORE - score.abs() + 1) / 2;
+    Some(if score > 0 { moves } else { -moves })
+}
+
+pub fn search(board: &Board, limits: SearchLimits) -> SearchResult {
+    Searcher::new(limits).search(board)
+}
+
+pub struct Searcher {
+    limits: SearchLimits,
+    tt: TranspositionTable,
+    killers: KillerMoves,
+    history: HistoryTable,
+    // Set from another thread to end the search early.
+    stop: Arc<AtomicBool>,
+    start: Instant,
+    nodes: u64,
+    stopped: bool,
+    // The best root move of the iteration in progress, so a search stopped
+    // before finishing its first iteration still has something to play.
+    root_best: Option<(Move, i32)>,
+}
+
+impl Searcher {
+    pub fn new(limits: SearchLimits) -> Self {
+        Self {
+            limits,
+            tt: TranspositionTable::default(),
+            killers: KillerMoves::new(),
+            history: HistoryTable::new(),
+            stop: Arc::new(AtomicBool::new(false)),
+            start: Instant::now(),
+            nodes: 0,
+            stopped: false,
+            root_best: None,
+        }
+    }
+
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.limits = limits;
+    }
+
+    // The table outlives a single search, so later searches from related
+    // positions start with what earlier ones learned.
+    pub fn tt(&self) -> &TranspositionTable {
+        &self.tt
+    }
+
+    pub fn tt_mut(&mut self) -> &mut TranspositionTable {
+        &mut self.tt
+    }
+
+    // Raising the flag stops the search the same way an exhausted limit does.
+    // It stays raised until the caller lowers it.
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop)
+    }
+
+    pub fn search(&mut self, board: &Board) -> SearchResult {
+        self.search_with_info(board, |_, _| {})
+    }
+
+    // Calls `on_iteration` with the result so far after every completed
+    // depth, for callers that report progress while the search runs.
+    pub fn search_with_info<F>(&mut self, board: &Board, mut on_iteration: F) -> SearchResult
+    where
+        F: FnMut(&Searcher, &SearchResult),
+    {
+        self.start = Instant::now();
+        self.nodes = 0;
+        self.stopped = false;
+        self.tt.new_search();
+        self.killers.clear();
+        self.history.age();
+
+        let mut board = board.clone();
+        let max_depth = self
+            .limits
+            .depth
+            .unwrap_or(MAX_PLY as u32)
+            .clamp(1, MAX_PLY as u32);
+        let mut result = SearchResult {
+            best_move: None,
+            score: 0,
+            depth: 0,
+            pv: Vec::new(),
+            nodes: 0,
+            elapsed: Duration::ZERO,
+        };
+
+        for depth in 1..=max_depth {
+            self.root_best = None;
+            let mut pv = Vec::new();
+            let score = self.negamax(&mut board, depth, 0, -INFINITY, INFINITY, &mut pv);
+            if self.stopped {
+                break;
+            }
+            self.complete_pv(&mut board, &mut pv, depth as usize);
+
+            result.best_move = pv.first().copied();
+            result.score = score;
+            result.depth = depth;
+            result.pv = pv;
+            result.nodes = self.nodes;
+            result.elapsed = self.start.elapsed();
+            on_iteration(self, &result);
+
+            // A mate inside the searched horizon cannot get any shorter.
+            if is_mate_score(score) && (MATE_SCORE - score.abs()) as u32 <= depth {
+                break;
+            }
+        }
+
+        // Stopped during the first iteration: play the best move found so
+        // far, or failing that any legal move.
+        if result.best_move.is_none() {
+            let fallback = self.root_best.or_else(|| {
+                board
+                    .generate_moves()
+                    .first()
+                    .map(|&mv| (mv, eval::evaluate(&board)))
+            });
+            if let Some((mv, score)) = fallback {
+                result.best_move = Some(mv);
+                result.score = score;
+                result.pv = vec![mv];
+            }
+        }
+
+        result.nodes = self.nodes;
+        result.elapsed = self.start.elapsed();
+        result
+    }
+
+    fn negamax(
+        &mut self,
+        board: &mut Board,
+        depth: u32,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        pv.clear();
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+
+        if ply > 0 && is_draw(board) {
+            return 0;
+        }
+
+        // The root always searches its moves so that it has a line to report.
+        let key = board.zobrist_key();
+        let mut hash_move = None;
+        if let Some(entry) = self.tt.probe(key, ply) {
+            hash_move = entry.best_move;
+            let usable = ply > 0
+                && entry.depth >= depth
+                && match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => entry.score >= beta,
+                    Bound::Upper => entry.score <= alpha,
+                };
+            if usable {
+                if entry.bound == Bound::Exact {
+                    pv.extend(entry.best_move);
+                }
+                return entry.score.clamp(alpha, beta);
+            }
+        }
+
+        let in_check = check::is_in_check(board, board.side_to_move);
+        // Searching one ply deeper when in check keeps forced lines from
+        // disappearing over the horizon.
+        let depth = if in_check { depth + 1 } else { depth };
+        if depth == 0 || ply >= MAX_PLY {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+
+        let moves = board.generate_moves();
+        if moves.is_empty() {
+            return if in_check {
+                -MATE_SCORE + ply as i32
+            } else {
+                0
+            };
+        }
+
+        let original_alpha = alpha;
+        let mut line = Vec::new();
+        let mut picker = MovePicker::new(moves, hash_move, self.killers.get(ply));
+        while let Some(mv) = picker.next(board, &self.history) {
+            board.make_move_unchecked(mv);
+            let score = -self.negamax(board, depth - 1, ply + 1, -beta, -alpha, &mut line);
+            board.unmake_move();
+
+            if self.stopped {
+                return 0;
+            }
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push(mv);
+                pv.extend_from_slice(&line);
+                if ply == 0 {
+                    self.root_best = Some((mv, score));
+                }
+                if alpha >= beta {
+                    if !mv.is_capture() && mv.promotion().is_none() {
+                        self.killers.store(ply, mv);
+                        self.history.reward(board.side_to_move, mv, depth);
+                    }
+                    break;
+                }
+            }
+        }
+
+        let bound = if alpha >= beta {
+            Bound::Lower
+        } else if alpha > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        let best_move = if bound == Bound::Upper {
+            None
+        } else {
+            pv.first().copied()
+        };
+        self.tt.store(key, depth, bound, alpha, best_move, ply);
+
+        alpha
+    }
+
+    // Table cutoffs cut the line short, so finish it from the stored moves.
+    fn complete_pv(&mut self, board: &mut Board, pv: &mut Vec<Move>, depth: usize) {
+        for &mv in pv.iter() {
+            board.make_move_unchecked(mv);
+        }
+
+        while pv.len() < depth && board.repetition_count() < 2 {
+            let next = self
+                .tt
+                .peek(board.zobrist_key(), pv.len())
+                .and_then(|entry| entry.best_move)
+                .filter(|mv| board.generate_moves().contains(mv));
+            match next {
+                Some(mv) => {
+                    board.make_move_unchecked(mv);
+                    pv.push(mv);
+                }
+                None => break,
+            }
+        }
+
+        for _ in 0..pv.len() {
+            board.unmake_move();
+        }
+    }
+
+    // Only captures and promotions are searched here, so the static
+    // evaluation is never taken in the middle of an exchange. In check there
+    // is no standing pat: every evasion is searched, and having none is mate.
+    fn quiescence(&mut self, board: &mut Board, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+
+        if ply >= MAX_PLY {
+            return eval::evaluate(board);
+        }
+
+        let moves = board.generate_moves();
+        let mut picker = if check::is_in_check(board, board.side_to_move) {
+            if moves.is_empty() {
+                return -MATE_SCORE + ply as i32;
+            }
+            MovePicker::new(moves, None, [None; 2])
+        } else {
+            let stand_pat = eval::evaluate(board);
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+            alpha = alpha.max(stand_pat);
+            MovePicker::captures(moves)
+        };
+        while let Some(mv) = picker.next(board, &self.history) {
+            board.make_move_unchecked(mv);
+            let score = -self.quiescence(board, ply + 1, -beta, -alpha);
+            board.unmake_move();
+
+            if self.stopped {
+                return 0;
+            }
+            if score > alpha {
+                alpha = score;
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+
+        alpha
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+
+        let stop_requested = self.stop.load(Ordering::Relaxed);
+        let out_of_nodes = self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes);
+        let out_of_time = self.nodes.is_multiple_of(TIME_CHECK_INTERVAL)
+            && self
+                .limits
+                .time
+                .is_some_and(|time| self.start.elapsed() >= time);
+        self.stopped = stop_requested || out_of_nodes || out_of_time;
+        self.stopped
+    }
+}
+
+// Repeating a position once is enough to treat it as a draw inside the
+// search: if it was worth repeating, it is worth repeating again.
+fn is_draw(board: &Board) -> bool {
+    board.halfmove_clock >= 100
+        || board.repetition_count() >= 2
+        || game_status::is_insufficient_material(board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::Square;
+    use crate::movement::san::parse_san;
+
+    fn best_move(fen: &str, limits: SearchLimits) -> SearchResult {
+        search(&Board::from_fen(fen).unwrap(), limits)
+    }
+
+    fn parse(board: &Board, san: &str) -> Move {
+        parse_san(board, san).unwrap()
+    }
+
+    #[test]
+    fn test_finds_mate_in_one() {
+        let result = best_move("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", SearchLimits::depth(3));
+
+        let mv = result.best_move.unwrap();
+        assert_eq!((mv.from(), mv.to()), (Square::A1, Square::A8));
+        assert_eq!(result.score, MATE_SCORE - 1);
+        assert_eq!(result.mate_in(), Some(1));
+        // The search stops as soon as the mate is proven.
+        assert_eq!(result.depth, 1);
+    }
+
+    #[test]
+    fn test_finds_mate_in_two() {
+        let result = best_move("k7/8/2K5/8/8/8/8/7R w - - 0 1", SearchLimits::depth(5));
+
+        assert_eq!(result.mate_in(), Some(2));
+        assert_eq!(result.pv.len(), 3);
+
+        let mut board = Board::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        for &mv in &result.pv {
+            board.play_move(mv).unwrap();
+        }
+        assert_eq!(
+            game_status::get_game_status(&board),
+            game_status::GameStatus::Checkmate
+        );
+    }
+
+    #[test]
+    fn test_mated_and_stalemated_roots() {
+        let mated = best_move("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1", SearchLimits::depth(3));
+        assert_eq!(mated.best_move, None);
+        assert_eq!(mated.score, -MATE_SCORE);
+        assert_eq!(mated.mate_in(), Some(0));
+
+        let stalemate = best_move("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1", SearchLimits::depth(3));
+        assert_eq!(stalemate.best_move, None);
+        assert_eq!(stalemate.score, 0);
+    }
+
+    #[test]
+    fn test_wins_material_and_sees_recaptures() {
+        let hanging = best_move("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", SearchLimits::depth(1));
+        let mv = hanging.best_move.unwrap();
+        assert_eq!((mv.from(), mv.to()), (Square::D2, Square::D5));
+        assert!(hanging.score > 300);
+
+        // Qxd5 looks like a free pawn at depth one until quiescence plays cxd5.
+        let defended = best_move("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1", SearchLimits::depth(1));
+        assert_ne!(defended.best_move.unwrap().to(), Square::D5);
+    }
+
+    #[test]
+    fn test_repetition_counts_as_a_draw() {
+        let mut board = Board::new();
+        assert!(!is_draw(&board));
+
+        for san in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+            board.play_move(parse(&board, san)).unwrap();
+        }
+        assert!(is_draw(&board));
+    }
+
+    #[test]
+    fn test_node_and_time_limits() {
+        let board = Board::new();
+
+        let by_nodes = search(&board, SearchLimits::nodes(5_000));
+        assert!(by_nodes.best_move.is_some());
+        assert!(by_nodes.depth >= 1);
+
+        let start = Instant::now();
+        let by_time = search(&board, SearchLimits::time(Duration::from_millis(50)));
+        assert!(by_time.best_move.is_some());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(by_time.pv.first().copied(), by_time.best_move);
+    }
+
+    #[test]
+    fn test_limits_hold_during_the_first_iteration() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+
+        let result = search(&board, SearchLimits::nodes(1));
+        assert_eq!(result.depth, 0);
+        assert!(result.nodes <= 1);
+        assert!(result
+            .best_move
+            .is_some_and(|mv| board.generate_moves().contains(&mv)));
+
+        // A few hundred nodes do not finish depth one here, but the root
+        // moves searched so far still pick the answer.
+        let result = search(&board, SearchLimits::nodes(300));
+        assert!(result.nodes <= 300);
+        assert_eq!(result.depth, 0);
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn test_quiescence_searches_check_evasions() {
+        let mut searcher = Searcher::new(SearchLimits::default());
+
+        // Fool's mate: in check with no evasions is mate, not a stand pat.
+        let mut mated =
+            Board::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+                .unwrap();
+        assert_eq!(
+            searcher.quiescence(&mut mated, 0, -INFINITY, INFINITY),
+            -MATE_SCORE
+        );
+
+        // Qxf7 is a capture that mates, so quiescence alone finds it.
+        let mut scholars =
+            Board::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4")
+                .unwrap();
+        assert_eq!(
+            searcher.quiescence(&mut scholars, 0, -INFINITY, INFINITY),
+            MATE_SCORE - 1
+        );
+    }
+
+    #[test]
+    fn test_stop_flag_and_iteration_reports() {
+        let board = Board::new();
+        let mut searcher = Searcher::new(SearchLimits::depth(3));
+
+        let mut reported = Vec::new();
+        let result = searcher.search_with_info(&board, |searcher, info| {
+            assert!(searcher.tt().stats().stores > 0);
+            reported.push((info.depth, info.best_move));
+        });
+        assert_eq!(
+            reported.iter().map(|&(depth, _)| depth).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        assert_eq!(reported.last(), Some(&(3, result.best_move)));
+
+        // With no limits only the flag ends the search, and a search stopped
+        // before its first iteration still plays a legal move.
+        searcher.set_limits(SearchLimits::default());
+        searcher.stop_flag().store(true, Ordering::Relaxed);
+        let stopped = searcher.search(&board);
+        assert_eq!(stopped.depth, 0);
+        assert_eq!(stopped.nodes, 0);
+        assert!(stopped
+            .best_move
+            .is_some_and(|mv| board.generate_moves().contains(&mv)));
+        assert_eq!(stopped.pv.first().copied(), stopped.best_move);
+    }
+
+    #[test]
+    fn test_transposition_table_carries_over_between_searches() {
+        let board =
+            Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3")
+                .unwrap();
+        let mut searcher = Searcher::new(SearchLimits::depth(3));
+
+        let first = searcher.search(&board);
+        assert!(searcher.tt().stats().hits > 0);
+        assert_eq!(first.pv.len(), 3);
+
+        searcher.tt_mut().reset_stats();
+        let second = searcher.search(&board);
+        assert!(second.nodes < first.nodes);
+        assert_eq!(second.score, first.score);
+        assert!(searcher.tt().stats().hit_rate() > 0.0);
+    }
+
+    #[test]
+    fn test_move_ordering_keeps_busy_positions_cheap() {
+        // Kiwipete: 48 moves at the root and captures everywhere.
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+
+        let result = search(&board, SearchLimits::depth(4));
+        assert_eq!(result.depth, 4);
+        assert!(result.nodes < 100_000, "searched {} nodes", result.nodes);
+    }
+
+    #[test]
+    fn test_mate_in_conversion() {
+        assert_eq!(mate_in(MATE_SCORE - 1), Some(1));
+        assert_eq!(mate_in(MATE_SCORE - 3), Some(2));
+        assert_eq!(mate_in(-MATE_SCORE + 2), Some(-1));
+        assert_eq!(mate_in(250), None);
+    }
+}
@@ -0,0 +1,365 @@
+use super::see::see;
+use super::MAX_PLY;
+use crate::board::Board;
+use crate::movement::moves::{Move, MoveList};
+use crate::pieces::piece_type::{Color, PieceType};
+
+// History scores are halved once any of them passes this, so old cutoffs
+// fade out instead of dominating forever.
+const MAX_HISTORY: i32 = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    HashMove,
+    GoodCaptures,
+    Killers,
+    Quiets,
+    BadCaptures,
+    Done,
+}
+
+// Most valuable victim, least valuable attacker: PxQ first, KxP last.
+// Promotions rank with captures and count the piece they promote to.
+pub fn mvv_lva(board: &Board, mv: Move) -> i32 {
+    let victim = if mv.is_en_passant() {
+        Some(PieceType::Pawn)
+    } else {
+        board
+            .piece_at(mv.to())
+            .filter(|_| !mv.is_castling())
+            .map(|piece| piece.piece_type)
+    };
+    let attacker = board
+        .piece_at(mv.from())
+        .map_or(PieceType::Pawn, |piece| piece.piece_type);
+
+    let victim_rank = victim.map_or(0, |piece_type| capture_rank(piece_type) + 1);
+    let promotion_rank = mv
+        .promotion()
+        .map_or(0, |piece_type| capture_rank(piece_type) + 1);
+    (victim_rank + promotion_rank) * 8 - capture_rank(attacker)
+}
+
+fn capture_rank(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    }
+}
+
+fn is_tactical(mv: Move) -> bool {
+    mv.is_capture() || mv.promotion().is_some()
+}
+
+// Two quiet moves per ply that recently caused a beta cutoff; a move that
+// refutes one line often refutes its siblings too.
+pub struct KillerMoves {
+    slots: [[Option<Move>; 2]; MAX_PLY],
+}
+
+impl KillerMoves {
+    pub fn new() -> Self {
+        Self {
+            slots: [[None; 2]; MAX_PLY],
+        }
+    }
+
+    pub fn get(&self, ply: usize) -> [Option<Move>; 2] {
+        self.slots.get(ply).copied().unwrap_or([None; 2])
+    }
+
+    pub fn store(&mut self, ply: usize, mv: Move) {
+        if let Some(slot) = self.slots.get_mut(ply) {
+            if slot[0] != Some(mv) {
+                slot[1] = slot[0];
+                slot[0] = Some(mv);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.slots = [[None; 2]; MAX_PLY];
+    }
+}
+
+impl Default for KillerMoves {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// How often a quiet move, by side, origin and destination, has caused a
+// cutoff anywhere in the tree, weighted towards deeper searches.
+pub struct HistoryTable {
+    scores: Vec<[[i32; 64]; 64]>,
+}
+
+impl HistoryTable {
+    pub fn new() -> Self {
+        Self {
+            scores: vec![[[0; 64]; 64]; 2],
+        }
+    }
+
+    pub fn get(&self, color: Color, mv: Move) -> i32 {
+        self.scores[color as usize][mv.from().index()][mv.to().index()]
+    }
+
+    pub fn reward(&mut self, color: Color, mv: Move, depth: u32) {
+        let score = &mut self.scores[color as usize][mv.from().index()][mv.to().index()];
+        *score += (depth * depth) as i32;
+        if *score > MAX_HISTORY {
+            self.age();
+        }
+    }
+
+    pub fn age(&mut self) {
+        for score in self.scores.iter_mut().flatten().flatten() {
+            *score /= 2;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for score in self.scores.iter_mut().flatten().flatten() {
+            *score = 0;
+        }
+    }
+}
+
+impl Default for HistoryTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Hands out legal moves best-first in stages, so a node that cuts off early
+// never pays to score the moves it did not try: the hash move, captures that
+// do not lose material by MVV-LVA, killers, quiet moves by history, and
+// finally captures that SEE says lose material.
+pub struct MovePicker {
+    moves: MoveList,
+    hash_move: Option<Move>,
+    killers: [Option<Move>; 2],
+    captures_only: bool,
+    stage: Stage,
+    scored: Vec<(Move, i32)>,
+    bad_captures: Vec<Move>,
+    next_killer: usize,
+    next_bad_capture: usize,
+}
+
+impl MovePicker {
+    pub fn new(moves: MoveList, hash_move: Option<Move>, killers: [Option<Move>; 2]) -> Self {
+        let hash_move = hash_move.filter(|mv| moves.contains(mv));
+        Self {
+            moves,
+            hash_move,
+            killers,
+            captures_only: false,
+            stage: Stage::HashMove,
+            scored: Vec::new(),
+            bad_captures: Vec::new(),
+            next_killer: 0,
+            next_bad_capture: 0,
+        }
+    }
+
+    // For quiescence: captures and promotions only, and captures that lose
+    // material are dropped rather than tried last.
+    pub fn captures(moves: MoveList) -> Self {
+        let mut picker = Self::new(moves, None, [None; 2]);
+        picker.captures_only = true;
+        picker
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    pub fn next(&mut self, board: &Board, history: &HistoryTable) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::HashMove => {
+                    self.enter(Stage::GoodCaptures, board, history);
+                    if self.hash_move.is_some() {
+                        return self.hash_move;
+                    }
+                }
+                Stage::GoodCaptures => match self.pick_best() {
+                    Some(mv) if mv.promotion().is_some() || see(board, mv) >= 0 => return Some(mv),
+                    Some(mv) => self.bad_captures.push(mv),
+                    None if self.captures_only => self.stage = Stage::Done,
+                    None => self.enter(Stage::Killers, board, history),
+                },
+                Stage::Killers => {
+                    while self.next_killer < self.killers.len() {
+                        let killer = self.killers[self.next_killer];
+                        self.next_killer += 1;
+                        if let Some(mv) = killer.filter(|&mv| self.is_playable_killer(mv)) {
+                            return Some(mv);
+                        }
+                    }
+                    self.enter(Stage::Quiets, board, history);
+                }
+                Stage::Quiets => match self.pick_best() {
+                    Some(mv) => return Some(mv),
+                    None => self.stage = Stage::BadCaptures,
+                },
+                Stage::BadCaptures => match self.bad_captures.get(self.next_bad_capture) {
+                    Some(&mv) => {
+                        self.next_bad_capture += 1;
+                        return Some(mv);
+                    }
+                    None => self.stage = Stage::Done,
+                },
+                Stage::Done => return None,
+            }
+        }
+    }
+
+    fn enter(&mut self, stage: Stage, board: &Board, history: &HistoryTable) {
+        self.stage = stage;
+        self.scored.clear();
+
+        for &mv in self.moves.iter() {
+            if Some(mv) == self.hash_move {
+                continue;
+            }
+            match stage {
+                Stage::GoodCaptures if is_tactical(mv) => {
+                    self.scored.push((mv, mvv_lva(board, mv)));
+                }
+                Stage::Quiets if !is_tactical(mv) && !self.killers.contains(&Some(mv)) => {
+                    self.scored.push((mv, history.get(board.side_to_move, mv)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // One selection-sort step: most nodes only ever look at the first few.
+    fn pick_best(&mut self) -> Option<Move> {
+        let best = self
+            .scored
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, (_, score))| *score)
+            .map(|(index, _)| index)?;
+        Some(self.scored.swap_remove(best).0)
+    }
+
+    fn is_playable_killer(&self, mv: Move) -> bool {
+        Some(mv) != self.hash_move && !is_tactical(mv) && self.moves.contains(&mv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::Square;
+    use crate::movement::san::parse_san;
+
+    fn parse(board: &Board, san: &str) -> Move {
+        parse_san(board, san).unwrap()
+    }
+
+    fn picked(picker: &mut MovePicker, board: &Board, history: &HistoryTable) -> Vec<Move> {
+        let mut moves = Vec::new();
+        while let Some(mv) = picker.next(board, history) {
+            moves.push(mv);
+        }
+        moves
+    }
+
+    #[test]
+    fn test_mvv_lva() {
+        let board = Board::from_fen("4k3/8/1q1r4/P7/2N5/8/8/4K3 w - - 0 1").unwrap();
+
+        let pawn_takes_queen = mvv_lva(&board, parse(&board, "axb6"));
+        let knight_takes_queen = mvv_lva(&board, parse(&board, "Nxb6"));
+        let knight_takes_rook = mvv_lva(&board, parse(&board, "Nxd6"));
+        assert!(pawn_takes_queen > knight_takes_queen);
+        assert!(knight_takes_queen > knight_takes_rook);
+        assert!(knight_takes_rook > mvv_lva(&board, parse(&board, "Ke2")));
+    }
+
+    #[test]
+    fn test_picks_moves_in_stages() {
+        // Rxd5 wins a pawn, Qxb7 loses the queen, and the rest are quiet.
+        let board = Board::from_fen("1r2k3/1p6/8/3p4/8/8/8/1Q1RK3 w - - 0 1").unwrap();
+        let hash_move = parse(&board, "Kf2");
+        let killer = parse(&board, "Rd4");
+        let favourite = parse(&board, "Qa2");
+        let mut history = HistoryTable::new();
+        history.reward(Color::White, favourite, 4);
+
+        let mut picker = MovePicker::new(
+            board.generate_moves(),
+            Some(hash_move),
+            [Some(killer), None],
+        );
+        let order = picked(&mut picker, &board, &history);
+
+        assert_eq!(order.len(), board.generate_moves().len());
+        assert_eq!(order[0], hash_move);
+        assert_eq!(order[1], parse(&board, "Rxd5"));
+        assert_eq!(order[2], killer);
+        assert_eq!(order[3], favourite);
+        assert_eq!(order.last(), Some(&parse(&board, "Qxb7")));
+        assert_eq!(picker.stage(), Stage::Done);
+    }
+
+    #[test]
+    fn test_capture_picker_skips_losing_captures() {
+        let board = Board::from_fen("1r2k3/1p6/8/3p4/8/8/8/1Q1RK3 w - - 0 1").unwrap();
+        let mut picker = MovePicker::captures(board.generate_moves());
+
+        let order = picked(&mut picker, &board, &HistoryTable::new());
+        assert_eq!(order, vec![parse(&board, "Rxd5")]);
+    }
+
+    #[test]
+    fn test_stale_hash_move_and_killers_are_ignored() {
+        let board = Board::new();
+        let elsewhere = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let bogus = parse(&elsewhere, "Ra8+");
+
+        let mut picker = MovePicker::new(board.generate_moves(), Some(bogus), [Some(bogus), None]);
+        let order = picked(&mut picker, &board, &HistoryTable::new());
+        assert_eq!(order.len(), 20);
+        assert!(!order.contains(&bogus));
+    }
+
+    #[test]
+    fn test_killers_and_history() {
+        let board = Board::new();
+        let e4 = parse(&board, "e4");
+        let d4 = parse(&board, "d4");
+
+        let mut killers = KillerMoves::new();
+        killers.store(3, e4);
+        killers.store(3, e4);
+        killers.store(3, d4);
+        assert_eq!(killers.get(3), [Some(d4), Some(e4)]);
+        assert_eq!(killers.get(MAX_PLY + 5), [None, None]);
+        killers.clear();
+        assert_eq!(killers.get(3), [None, None]);
+
+        let mut history = HistoryTable::new();
+        history.reward(Color::White, e4, 3);
+        history.reward(Color::White, e4, 2);
+        assert_eq!(history.get(Color::White, e4), 13);
+        assert_eq!(history.get(Color::Black, e4), 0);
+        history.age();
+        assert_eq!(history.get(Color::White, e4), 6);
+
+        let knight = Move::new(Square::G1, Square::F3, None, Default::default());
+        history.reward(Color::White, knight, 1100);
+        assert!(history.get(Color::White, knight) <= MAX_HISTORY);
+        assert_eq!(history.get(Color::White, e4), 3);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::bitboard::attacks;
+use crate::bitboard::{Bitboard, Square};
+use crate::board::Board;
+use crate::movement::moves::Move;
+use crate::pieces::piece_type::{Color, PieceType};
+
+// Indexed by PieceType: pawn, knight, bishop, rook, king, queen. A king is
+// never left to be captured, so its value only has to be large.
+const SEE_VALUES: [i32; 6] = [100, 320, 330, 500, 20_000, 950];
+
+// Least valuable attacker first.
+const CAPTURE_ORDER: [PieceType; 6] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+    PieceType::King,
+];
+
+pub fn piece_value(piece_type: PieceType) -> i32 {
+    SEE_VALUES[piece_type as usize]
+}
+
+// Static exchange evaluation: the material the side to move ends up with,
+// in centipawns, if both sides keep recapturing on the destination square
+// with their cheapest piece and either may stop when that suits them.
+// Quiet moves count too, so a negative result means the move hangs material.
+// Pins are ignored. Castling never loses material, and in Chess960 its
+// destination is the king's own rook, so it is scored as 0 up front.
+pub fn see(board: &Board, mv: Move) -> i32 {
+    if mv.is_castling() {
+        return 0;
+    }
+    let mover = match board.piece_at(mv.from()) {
+        Some(piece) => piece,
+        None => return 0,
+    };
+    let to = mv.to();
+
+    let mut occupancy = board.all_pieces().0 & !mv.from().bitboard().0;
+    let captured = if mv.is_en_passant() {
+        let captured_square = Square::from_file_rank(to.file(), mv.from().rank());
+        occupancy &= !captured_square.bitboard().0;
+        piece_value(PieceType::Pawn)
+    } else {
+        board
+            .piece_at(to)
+            .map_or(0, |piece| piece_value(piece.piece_type))
+    };
+
+    let mut gains = [0i32; 32];
+    gains[0] = captured;
+    let mut on_square = piece_value(mover.piece_type);
+    if let Some(promotion) = mv.promotion() {
+        gains[0] += piece_value(promotion) - piece_value(PieceType::Pawn);
+        on_square = piece_value(promotion);
+    }
+
+    let mut side = mover.color.opposite();
+    let mut depth = 0;
+    loop {
+        let attackers = attackers_to(board, to, occupancy) & occupancy;
+        let (square, piece_type) = match least_valuable_attacker(board, attackers, side) {
+            Some(attacker) => attacker,
+            None => break,
+        };
+
+        // A king may only recapture when nothing can take it back.
+        let remaining = occupancy & !square.bitboard().0;
+        if piece_type == PieceType::King
+            && attackers_to(board, to, remaining) & remaining & board.occupancy(side.opposite()).0
+                != 0
+        {
+            break;
+        }
+
+        depth += 1;
+        gains[depth] = on_square - gains[depth - 1];
+        on_square = piece_value(piece_type);
+        occupancy = remaining;
+        side = side.opposite();
+
+        if depth == gains.len() - 1 {
+            break;
+        }
+    }
+
+    // Unwind from the last capture: each side only recaptures if it gains.
+    while depth > 0 {
+        gains[depth - 1] = -(-gains[depth - 1]).max(gains[depth]);
+        depth -= 1;
+    }
+    gains[0]
+}
+
+fn attackers_to(board: &Board, square: Square, occupancy: u64) -> u64 {
+    let index = square.index();
+    let both = |piece_type| {
+        (board.pieces(piece_type, Color::White) | board.pieces(piece_type, Color::Black)).0
+    };
+    let queens = both(PieceType::Queen);
+
+    (attacks::pawn_attacks(Color::Black, index) & board.pieces(PieceType::Pawn, Color::White).0)
+        | (attacks::pawn_attacks(Color::White, index)
+            & board.pieces(PieceType::Pawn, Color::Black).0)
+        | (attacks::knight_attacks(index) & both(PieceType::Knight))
+        | (attacks::king_attacks(index) & both(PieceType::King))
+        | (attacks::bishop_attacks(index, occupancy) & (both(PieceType::Bishop) | queens))
+        | (attacks::rook_attacks(index, occupancy) & (both(PieceType::Rook) | queens))
+}
+
+fn least_valuable_attacker(
+    board: &Board,
+    attackers: u64,
+    color: Color,
+) -> Option<(Square, PieceType)> {
+    CAPTURE_ORDER.iter().find_map(|&piece_type| {
+        (Bitboard(attackers) & board.pieces(piece_type, color))
+            .lsb()
+            .map(|square| (square, piece_type))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::movement::san::parse_san;
+
+    fn see_of(fen: &str, san: &str) -> i32 {
+        let board = Board::from_fen(fen).unwrap();
+        see(&board, parse_san(&board, san).unwrap())
+    }
+
+    #[test]
+    fn test_simple_exchanges() {
+        // An undefended pawn.
+        assert_eq!(
+            see_of("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "Rxe5"),
+            100
+        );
+        // Knight takes a pawn defended by a pawn.
+        assert_eq!(
+            see_of("4k3/8/3p4/4p3/8/5N2/8/4K3 w - - 0 1", "Nxe5"),
+            100 - 320
+        );
+        // Pawn takes a defended knight.
+        assert_eq!(
+            see_of("4k3/8/3p4/4n3/3P4/8/8/4K3 w - - 0 1", "dxe5"),
+            320 - 100
+        );
+    }
+
+    #[test]
+    fn test_x_ray_recaptures() {
+        // The queen behind the rook wins the exchange on d5.
+        let fen = "6k1/3r4/8/3n4/8/8/3R4/3Q2K1 w - - 0 1";
+        assert_eq!(see_of(fen, "Rxd5"), 320);
+
+        // Without it the rook goes for the knight.
+        let fen = "6k1/3r4/8/3n4/8/8/3R4/6K1 w - - 0 1";
+        assert_eq!(see_of(fen, "Rxd5"), 320 - 500);
+
+        // A black queen behind the rook makes Qxd5 a mistake, so White
+        // stops after losing the rook for the knight.
+        let fen = "3q2k1/3r4/8/3n4/8/8/3R4/3Q2K1 w - - 0 1";
+        assert_eq!(see_of(fen, "Rxd5"), 320 - 500);
+    }
+
+    #[test]
+    fn test_quiet_moves_and_king_recaptures() {
+        assert_eq!(see_of("4k3/8/8/3p4/8/8/8/3QK3 w - - 0 1", "Qd4"), 0);
+        assert_eq!(see_of("4k3/8/2p5/8/8/8/8/3QK3 w - - 0 1", "Qd5"), -950);
+
+        // The king only recaptures when nothing can take it back.
+        assert_eq!(see_of("4k3/3r4/8/8/8/8/8/3RK3 w - - 0 1", "Rxd7+"), 0);
+        assert_eq!(see_of("4k3/3r4/8/1B6/8/8/8/3RK3 w - - 0 1", "Rxd7"), 500);
+        assert_eq!(see_of("8/8/8/8/8/8/3k4/3Q1K2 b - - 0 1", "Kxd1"), 950);
+    }
+
+    #[test]
+    fn test_en_passant_and_promotion() {
+        assert_eq!(see_of("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "exd6"), 100);
+        assert_eq!(see_of("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a8=Q+"), 950 - 100);
+        assert_eq!(
+            see_of("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "bxa8=Q+"),
+            500 + 950 - 100
+        );
+    }
+
+    #[test]
+    fn test_castling_is_even() {
+        // The king lands where the rook stood, which the black rook attacks.
+        assert_eq!(see_of("1k6/r7/8/8/8/8/8/RK6 w A - 0 1", "O-O-O"), 0);
+        assert_eq!(see_of("4k3/8/8/8/8/8/8/4K2R w K - 0 1", "O-O"), 0);
+    }
+}
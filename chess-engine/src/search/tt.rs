@@ -0,0 +1,289 @@
+use super::{MATE_SCORE, MAX_PLY};
+use crate::movement::moves::Move;
+use std::mem;
+
+pub const DEFAULT_SIZE_MB: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    // The score is at least this much: the node failed high.
+    Lower,
+    // The score is at most this much: no move raised alpha.
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TtEntry {
+    pub key: u64,
+    pub depth: u32,
+    pub bound: Bound,
+    pub score: i32,
+    pub best_move: Option<Move>,
+    generation: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TtStats {
+    pub probes: u64,
+    pub hits: u64,
+    pub stores: u64,
+    // Stores that evicted an entry for a different position.
+    pub replacements: u64,
+}
+
+impl TtStats {
+    pub fn hit_rate(&self) -> f64 {
+        if self.probes == 0 {
+            0.0
+        } else {
+            self.hits as f64 / self.probes as f64
+        }
+    }
+}
+
+pub struct TranspositionTable {
+    entries: Vec<Option<TtEntry>>,
+    generation: u8,
+    stats: TtStats,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> Self {
+        let mut table = Self {
+            entries: Vec::new(),
+            generation: 0,
+            stats: TtStats::default(),
+        };
+        table.resize(size_mb);
+        table
+    }
+
+    // Resizing drops every stored entry.
+    pub fn resize(&mut self, size_mb: usize) {
+        let count = (size_mb * 1024 * 1024 / mem::size_of::<Option<TtEntry>>()).max(1);
+        self.entries = vec![None; count];
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+        self.generation = 0;
+        self.stats = TtStats::default();
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    // Marks everything stored so far as older than what the next search
+    // writes, so stale deep entries do not squat on their slots forever.
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    // Mate scores come back relative to `ply`, the distance from the root of
+    // the search doing the probe.
+    pub fn probe(&mut self, key: u64, ply: usize) -> Option<TtEntry> {
+        self.stats.probes += 1;
+        let entry = self.peek(key, ply)?;
+        self.stats.hits += 1;
+        Some(entry)
+    }
+
+    // Like probe, but left out of the statistics.
+    pub fn peek(&self, key: u64, ply: usize) -> Option<TtEntry> {
+        let entry = self.entries[self.index(key)].filter(|entry| entry.key == key)?;
+        Some(TtEntry {
+            score: score_from_tt(entry.score, ply),
+            ..entry
+        })
+    }
+
+    pub fn store(
+        &mut self,
+        key: u64,
+        depth: u32,
+        bound: Bound,
+        score: i32,
+        best_move: Option<Move>,
+        ply: usize,
+    ) {
+        let index = self.index(key);
+        let generation = self.generation;
+
+        // Depth-preferred within a search, but anything from an earlier search
+        // is fair game.
+        if let Some(existing) = self.entries[index] {
+            let same_position = existing.key == key;
+            if !same_position && existing.generation == generation && existing.depth > depth {
+                return;
+            }
+            if !same_position {
+                self.stats.replacements += 1;
+            }
+        }
+
+        // Keep the old move if this store has none, such as after a fail low.
+        let best_move = best_move.or_else(|| {
+            self.entries[index]
+                .filter(|existing| existing.key == key)
+                .and_then(|existing| existing.best_move)
+        });
+
+        self.entries[index] = Some(TtEntry {
+            key,
+            depth,
+            bound,
+            score: score_to_tt(score, ply),
+            best_move,
+            generation,
+        });
+        self.stats.stores += 1;
+    }
+
+    pub fn stats(&self) -> TtStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = TtStats::default();
+    }
+
+    // Per mille of slots in use, sampled from the start of the table the way
+    // UCI's hashfull expects.
+    pub fn hashfull(&self) -> u32 {
+        let sample = self.entries.len().min(1000);
+        let used = self.entries[..sample]
+            .iter()
+            .filter(|entry| entry.is_some())
+            .count();
+        (used * 1000 / sample) as u32
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key % self.entries.len() as u64) as usize
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_SIZE_MB)
+    }
+}
+
+// The search scores mates by distance from the root, but a stored position
+// can be reached at any ply, so entries count from the position itself.
+fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_SCORE - MAX_PLY as i32 {
+        score + ply as i32
+    } else if score <= -MATE_SCORE + MAX_PLY as i32 {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_SCORE - MAX_PLY as i32 {
+        score - ply as i32
+    } else if score <= -MATE_SCORE + MAX_PLY as i32 {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::Square;
+    use crate::movement::moves::MoveFlags;
+
+    fn quiet(from: Square, to: Square) -> Move {
+        Move::new(from, to, None, MoveFlags::QUIET)
+    }
+
+    #[test]
+    fn test_store_and_probe() {
+        let mut table = TranspositionTable::new(1);
+        let mv = quiet(Square::E2, Square::E4);
+
+        assert_eq!(table.probe(42, 0), None);
+        table.store(42, 5, Bound::Exact, 37, Some(mv), 0);
+
+        let entry = table.probe(42, 0).unwrap();
+        assert_eq!(
+            (entry.depth, entry.bound, entry.score, entry.best_move),
+            (5, Bound::Exact, 37, Some(mv))
+        );
+        assert_eq!(table.probe(42 + table.capacity() as u64, 0), None);
+
+        let stats = table.stats();
+        assert_eq!((stats.probes, stats.hits, stats.stores), (3, 1, 1));
+        assert!((stats.hit_rate() - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_mate_scores_are_stored_relative_to_the_node() {
+        let mut table = TranspositionTable::new(1);
+
+        // Mate in three plies from a node four plies below the root.
+        table.store(7, 3, Bound::Exact, MATE_SCORE - 7, None, 4);
+        // Reaching the same node at ply two, the mate is two plies closer.
+        assert_eq!(table.probe(7, 2).unwrap().score, MATE_SCORE - 5);
+
+        table.store(8, 3, Bound::Upper, -MATE_SCORE + 6, None, 6);
+        assert_eq!(table.probe(8, 1).unwrap().score, -MATE_SCORE + 1);
+
+        table.store(9, 3, Bound::Lower, 120, None, 6);
+        assert_eq!(table.probe(9, 1).unwrap().score, 120);
+    }
+
+    #[test]
+    fn test_replacement_policy() {
+        let mut table = TranspositionTable::new(1);
+        let rival = 5 + table.capacity() as u64;
+        let mv = quiet(Square::G1, Square::F3);
+
+        table.store(5, 8, Bound::Exact, 10, Some(mv), 0);
+        // A shallower entry for another position does not evict a deeper one.
+        table.store(rival, 2, Bound::Exact, 20, None, 0);
+        assert!(table.probe(5, 0).is_some());
+        assert_eq!(table.probe(rival, 0), None);
+
+        // The same position is always refreshed, keeping its move.
+        table.store(5, 1, Bound::Upper, -3, None, 0);
+        let entry = table.probe(5, 0).unwrap();
+        assert_eq!((entry.depth, entry.best_move), (1, Some(mv)));
+
+        // Entries from an earlier search give way to anything new.
+        table.store(5, 9, Bound::Exact, 10, Some(mv), 0);
+        table.new_search();
+        table.store(rival, 1, Bound::Exact, 20, None, 0);
+        assert_eq!(table.probe(5, 0), None);
+        assert_eq!(table.stats().replacements, 1);
+    }
+
+    #[test]
+    fn test_sizing_and_clearing() {
+        let mut table = TranspositionTable::new(1);
+        let per_mb = table.capacity();
+        assert!(per_mb > 10_000);
+
+        table.resize(4);
+        assert_eq!(
+            table.capacity(),
+            4 * 1024 * 1024 / mem::size_of::<Option<TtEntry>>()
+        );
+        assert_eq!(TranspositionTable::new(0).capacity(), 1);
+
+        for key in 0..500 {
+            table.store(key, 1, Bound::Exact, 0, None, 0);
+        }
+        assert_eq!(table.hashfull(), 500);
+
+        table.clear();
+        assert_eq!(table.hashfull(), 0);
+        assert_eq!(table.stats(), TtStats::default());
+    }
+}
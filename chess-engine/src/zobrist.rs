@@ -0,0 +1,201 @@
+use crate::bitboard::attacks::pawn_attacks;
+use crate::bitboard::{Bitboard, Square};
+use crate::board::{Board, CastlingRights};
+use crate::pieces::piece_type::{Color, PieceType};
+
+struct ZobristKeys {
+    pieces: [[[u64; 64]; 6]; 2],
+    side_to_move: u64,
+    castling: [u64; 16],
+    en_passant: [u64; 8],
+}
+
+static KEYS: ZobristKeys = generate_keys(0x0C4E_5353_C0DE_2025);
+
+const fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+const fn generate_keys(seed: u64) -> ZobristKeys {
+    let mut state = seed;
+    let mut keys = ZobristKeys {
+        pieces: [[[0; 64]; 6]; 2],
+        side_to_move: 0,
+        castling: [0; 16],
+        en_passant: [0; 8],
+    };
+
+    let mut color = 0;
+    while color < 2 {
+        let mut piece = 0;
+        while piece < 6 {
+            let mut square = 0;
+            while square < 64 {
+                keys.pieces[color][piece][square] = splitmix64(&mut state);
+                square += 1;
+            }
+            piece += 1;
+        }
+        color += 1;
+    }
+
+    keys.side_to_move = splitmix64(&mut state);
+
+    let mut i = 0;
+    while i < 16 {
+        keys.castling[i] = splitmix64(&mut state);
+        i += 1;
+    }
+
+    let mut file = 0;
+    while file < 8 {
+        keys.en_passant[file] = splitmix64(&mut state);
+        file += 1;
+    }
+
+    keys
+}
+
+pub fn piece_key(piece_type: PieceType, color: Color, square: Square) -> u64 {
+    KEYS.pieces[color as usize][piece_type as usize][square.index()]
+}
+
+pub fn side_to_move_key() -> u64 {
+    KEYS.side_to_move
+}
+
+pub fn castling_key(rights: CastlingRights) -> u64 {
+    let index = rights.white_kingside as usize
+        | (rights.white_queenside as usize) << 1
+        | (rights.black_kingside as usize) << 2
+        | (rights.black_queenside as usize) << 3;
+
+    KEYS.castling[index]
+}
+
+pub fn en_passant_key(square: Square) -> u64 {
+    KEYS.en_passant[square.file()]
+}
+
+// The en passant file only matters when the side to move has a pawn that can
+// capture, otherwise identical positions would hash differently.
+pub(crate) fn en_passant_component(board: &Board) -> u64 {
+    let square = match board.en_passant_square {
+        Some(square) => square,
+        None => return 0,
+    };
+
+    let pawns = board.pieces(PieceType::Pawn, board.side_to_move);
+    let attackers = Bitboard(pawn_attacks(board.side_to_move.opposite(), square.index()));
+
+    if !(pawns & attackers).is_empty() {
+        en_passant_key(square)
+    } else {
+        0
+    }
+}
+
+pub fn compute_hash(board: &Board) -> u64 {
+    let mut hash = 0u64;
+
+    for square in board.all_pieces() {
+        if let Some(piece) = board.piece_at(square) {
+            hash ^= piece_key(piece.piece_type, piece.color, square);
+        }
+    }
+
+    if board.side_to_move == Color::Black {
+        hash ^= side_to_move_key();
+    }
+
+    hash ^ castling_key(board.castling_rights) ^ en_passant_component(board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_are_distinct() {
+        let mut keys: Vec<u64> = KEYS.pieces.iter().flatten().flatten().copied().collect();
+        keys.push(KEYS.side_to_move);
+        keys.extend_from_slice(&KEYS.castling);
+        keys.extend_from_slice(&KEYS.en_passant);
+
+        let count = keys.len();
+        keys.sort_unstable();
+        keys.dedup();
+        assert_eq!(keys.len(), count);
+    }
+
+    #[test]
+    fn test_transposition_has_same_key() {
+        let mut first = Board::new();
+        for (from, to) in [
+            (Square::G1, Square::F3),
+            (Square::G8, Square::F6),
+            (Square::B1, Square::C3),
+            (Square::B8, Square::C6),
+        ] {
+            first.make_move(from, to).unwrap();
+        }
+
+        let mut second = Board::new();
+        for (from, to) in [
+            (Square::B1, Square::C3),
+            (Square::B8, Square::C6),
+            (Square::G1, Square::F3),
+            (Square::G8, Square::F6),
+        ] {
+            second.make_move(from, to).unwrap();
+        }
+
+        assert_eq!(first.zobrist_key(), second.zobrist_key());
+        assert_ne!(first.zobrist_key(), Board::new().zobrist_key());
+    }
+
+    #[test]
+    fn test_key_depends_on_side_castling_and_en_passant() {
+        let white = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let black = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+        let no_castling = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Kkq - 0 1").unwrap();
+
+        assert_ne!(white.zobrist_key(), black.zobrist_key());
+        assert_ne!(white.zobrist_key(), no_castling.zobrist_key());
+
+        let capturable = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        let without = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_ne!(capturable.zobrist_key(), without.zobrist_key());
+
+        let uncapturable = Board::from_fen("4k3/8/8/3p3P/8/8/8/4K3 w - d6 0 1").unwrap();
+        let without = Board::from_fen("4k3/8/8/3p3P/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(uncapturable.zobrist_key(), without.zobrist_key());
+    }
+
+    #[test]
+    fn test_incremental_key_matches_fen_over_game() {
+        let mut board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        let start_key = board.zobrist_key();
+
+        for ply in 0..60 {
+            let moves = board.generate_moves();
+            if moves.is_empty() {
+                break;
+            }
+            board.play_move(moves[(ply * 7) % moves.len()]).unwrap();
+
+            let reloaded = Board::from_fen(&board.to_fen()).unwrap();
+            assert_eq!(board.zobrist_key(), reloaded.zobrist_key());
+            assert_eq!(board.zobrist_key(), compute_hash(&board));
+        }
+
+        while board.unmake_move().is_some() {}
+        assert_eq!(board.zobrist_key(), start_key);
+    }
+}
@@ -1,134 +1,444 @@
 use chess_engine::{
-    board::Board,
-    game_state::game_status::{get_game_status, GameStatus},
-    pieces::piece_type::{Color, MoveError, PieceType},
+    bitboard::Square,
+    board::{Board, STARTING_FEN},
+    chess960, eval,
+    game::{Game, GameError},
+    game_state::game_status::{get_claimable_draw, get_game_status, GameStatus},
+    movement::moves::Move,
+    movement::san::{move_to_san, parse_san},
+    perft,
+    pgn::parse_pgn,
+    pieces::piece_type::{Color, MoveError, Piece, PieceType},
+    search::{self, SearchLimits, Searcher},
 };
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::fs;
 use std::io::{self, Write};
+use std::process;
+use std::time::{Duration, Instant};
+
+mod uci;
 
 #[derive(Parser)]
 #[clap(author, version, about = "A bitboard-based chess engine CLI")]
 struct Cli {
     #[clap(short, long, help = "Run in interactive mode")]
     interactive: bool,
+
+    #[clap(long, help = "Speak the Universal Chess Interface on stdin and stdout")]
+    uci: bool,
+
+    #[clap(
+        long,
+        value_enum,
+        default_value = "standard",
+        help = "Rules to play by; in chess960, castle by moving the king onto its rook"
+    )]
+    variant: Variant,
+
+    #[clap(
+        long,
+        help = "Chess960 start position from 0 to 959, random if omitted"
+    )]
+    position: Option<u32>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Variant {
+    Standard,
+    Chess960,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[clap(about = "Count leaf nodes per root move to debug move generation")]
+    Perft {
+        #[clap(help = "Position in FEN, or 'startpos'")]
+        fen: String,
+        #[clap(help = "Search depth in plies")]
+        depth: u32,
+    },
 }
 
-fn algebraic_to_index(notation: &str) -> Option<usize> {
-    if notation.len() != 2 {
+// The interactive engine stops at whichever of these it reaches first.
+const ENGINE_DEPTH: u32 = 6;
+const ENGINE_TIME: Duration = Duration::from_secs(3);
+
+fn char_to_promotion(symbol: char) -> Option<PieceType> {
+    match symbol {
+        'q' => Some(PieceType::Queen),
+        'r' => Some(PieceType::Rook),
+        'b' => Some(PieceType::Bishop),
+        'n' => Some(PieceType::Knight),
+        _ => None,
+    }
+}
+
+// Coordinate notation such as e2e4 or e7e8q: two squares and an optional
+// promotion letter. Anything else, like Bxf7+ or O-O-O, is left for SAN.
+fn parse_coordinates(input: &str) -> Option<(Square, Square, Option<char>)> {
+    if input.len() != 4 && input.len() != 5 {
         return None;
     }
+    let from = Square::from_algebraic(input.get(0..2)?)?;
+    let to = Square::from_algebraic(input.get(2..4)?)?;
+    Some((from, to, input.get(4..)?.chars().next()))
+}
 
-    let file = notation.chars().next()?;
-    let rank = notation.chars().nth(1)?;
+fn is_promotion_move(board: &Board, from: Square, to: Square) -> bool {
+    matches!(board.piece_at(from), Some(piece) if piece.piece_type == PieceType::Pawn)
+        && (to.rank() == 0 || to.rank() == 7)
+}
 
-    if !('a'..='h').contains(&file) || !('1'..='8').contains(&rank) {
-        return None;
+fn piece_name(piece: Piece) -> &'static str {
+    match (piece.color, piece.piece_type) {
+        (Color::White, PieceType::Pawn) => "White Pawn",
+        (Color::Black, PieceType::Pawn) => "Black Pawn",
+        (Color::White, PieceType::Knight) => "White Knight",
+        (Color::Black, PieceType::Knight) => "Black Knight",
+        (Color::White, PieceType::Bishop) => "White Bishop",
+        (Color::Black, PieceType::Bishop) => "Black Bishop",
+        (Color::White, PieceType::Rook) => "White Rook",
+        (Color::Black, PieceType::Rook) => "Black Rook",
+        (Color::White, PieceType::King) => "White King",
+        (Color::Black, PieceType::King) => "Black King",
+        (Color::White, PieceType::Queen) => "White Queen",
+        (Color::Black, PieceType::Queen) => "Black Queen",
     }
+}
+
+fn read_promotion_choice() -> Option<PieceType> {
+    print!("Promote to (q/r/b/n): ");
+    io::stdout().flush().unwrap();
 
-    let file_idx = (file as u8 - b'a') as usize;
-    let rank_idx = (rank as u8 - b'1') as usize;
+    let mut choice = String::new();
+    io::stdin().read_line(&mut choice).unwrap();
+    let choice = choice.trim().to_lowercase();
 
-    Some(rank_idx * 8 + file_idx)
+    let mut chars = choice.chars();
+    match (chars.next(), chars.next()) {
+        (Some(symbol), None) => char_to_promotion(symbol),
+        _ => None,
+    }
 }
 
-fn index_to_algebraic(index: usize) -> String {
-    let file = (index % 8) as u8 + b'a';
-    let rank = (index / 8) as u8 + b'1';
+fn make_move(
+    game: &mut Game,
+    from: Square,
+    to: Square,
+    promotion: Option<PieceType>,
+) -> Result<(), GameError> {
+    if game.is_over() {
+        return Err(GameError::GameOver);
+    }
+
+    let to = chess960_castling(game.board(), from, to).unwrap_or(to);
 
-    format!("{}{}", file as char, rank as char)
+    // Play it on a copy first to get a specific error, and the move's flags.
+    let mut board = game.board().clone();
+    match promotion {
+        Some(piece_type) => board.make_move_with_promotion(from, to, piece_type),
+        None => board.make_move(from, to),
+    }
+    .map_err(GameError::IllegalMove)?;
+
+    let mv = board.unmake_move().expect("a move was just played");
+    warn_if_hanging(&board, mv);
+    game.play_move(mv)
+}
+
+// Chess960 castling is entered as the king taking its own rook, e1h1. The
+// king's destination, e1g1, is accepted too when it can only mean castling;
+// this returns the rook's square to play instead.
+fn chess960_castling(board: &Board, from: Square, to: Square) -> Option<Square> {
+    if !board.chess960 {
+        return None;
+    }
+
+    let moves = board.generate_moves();
+    if moves
+        .iter()
+        .any(|mv| mv.from() == from && mv.to() == to && !mv.is_castling())
+    {
+        return None;
+    }
+    moves
+        .iter()
+        .find(|mv| {
+            let king_file = if mv.to().file() > mv.from().file() {
+                6
+            } else {
+                2
+            };
+            mv.is_castling()
+                && mv.from() == from
+                && Square::from_file_rank(king_file, from.rank()) == to
+        })
+        .map(|mv| mv.to())
+}
+
+fn warn_if_hanging(board: &Board, mv: Move) {
+    let exchange = search::see::see(board, mv);
+    if exchange < 0 {
+        println!(
+            "Warning: this move hangs a piece, the exchange on {} loses about {} centipawns",
+            mv.to(),
+            -exchange
+        );
+    }
 }
 
 fn display_move_error(error: MoveError) {
     match error {
-        MoveError::NoPieceAtSource => println!("Error: No piece at the source square"),
-        MoveError::WrongColorPiece => println!("Error: That's not your piece to move"),
-        MoveError::InvalidDestination => println!("Error: Invalid destination for this piece"),
-        MoveError::PathBlocked => println!("Error: The path is blocked by another piece"),
-        MoveError::DestinationOccupiedBySameColor => {
-            println!("Error: Destination is occupied by your own piece")
+        MoveError::InvalidPromotion => println!("Error: {}, e.g. 'e7e8q'", error),
+        error => println!("Error: {}", error),
+    }
+}
+
+fn display_game_error(error: GameError) {
+    match error {
+        GameError::IllegalMove(err) => display_move_error(err),
+        err => println!("Error: {}", err),
+    }
+}
+
+fn play_san(game: &mut Game, input: &str) {
+    if game.is_over() {
+        display_game_error(GameError::GameOver);
+        return;
+    }
+
+    let mv = match parse_san(game.board(), input) {
+        Ok(mv) => mv,
+        Err(err) => {
+            println!("Error: {}", err);
+            return;
+        }
+    };
+
+    warn_if_hanging(game.board(), mv);
+    play_and_show(game, mv);
+}
+
+fn play_and_show(game: &mut Game, mv: Move) {
+    let san = move_to_san(game.board(), mv);
+    match game.play_move(mv) {
+        Ok(_) => {
+            println!("Played {}", san);
+            print_game_status(game);
+            game.board().print();
         }
+        Err(err) => display_game_error(err),
     }
 }
 
-fn print_legal_moves(board: &Board, square: usize) {
-    let moves = board.get_legal_moves(square);
-    if moves == 0 {
-        println!("No legal moves for piece at {}", index_to_algebraic(square));
+fn play_engine_move(game: &mut Game, searcher: &mut Searcher) {
+    if game.is_over() {
+        display_game_error(GameError::GameOver);
         return;
     }
 
-    let piece_info = match board.get_piece_type_at(square) {
-        Some((PieceType::Pawn, Color::White)) => "White Pawn",
-        Some((PieceType::Pawn, Color::Black)) => "Black Pawn",
-        Some((PieceType::Knight, Color::White)) => "White Knight",
-        Some((PieceType::Knight, Color::Black)) => "Black Knight",
-        Some((PieceType::Bishop, Color::White)) => "White Bishop",
-        Some((PieceType::Bishop, Color::Black)) => "Black Bishop",
-        Some((PieceType::Rook, Color::White)) => "White Rook",
-        Some((PieceType::Rook, Color::Black)) => "Black Rook",
-        Some((PieceType::King, Color::White)) => "White King",
-        Some((PieceType::King, Color::Black)) => "Black King",
-        Some((PieceType::Queen, Color::White)) => "White Queen",
-        Some((PieceType::Queen, Color::Black)) => "Black Queen",
-        None => "No piece",
+    searcher.tt_mut().reset_stats();
+    let result = searcher.search(game.board());
+    let mv = match result.best_move {
+        Some(mv) => mv,
+        None => {
+            println!("The engine has no legal moves");
+            return;
+        }
     };
 
     println!(
-        "Legal moves for {} at {}:",
-        piece_info,
-        index_to_algebraic(square)
+        "Engine thought for {:.1}s: {} at depth {} ({} nodes)",
+        result.elapsed.as_secs_f64(),
+        format_score(result.score),
+        result.depth,
+        result.nodes
+    );
+    let stats = searcher.tt().stats();
+    println!(
+        "Hash table: {:.1}% hits over {} probes, {:.1}% full",
+        stats.hit_rate() * 100.0,
+        stats.probes,
+        searcher.tt().hashfull() as f64 / 10.0
     );
+    play_and_show(game, mv);
+}
 
-    let mut move_count = 0;
-    for i in 0..64 {
-        if (moves & (1u64 << i)) != 0 {
-            print!("{}) {} ", move_count + 1, index_to_algebraic(i));
-            move_count += 1;
-            if move_count % 8 == 0 {
-                println!();
-            }
-        }
+fn format_score(score: i32) -> String {
+    match search::mate_in(score) {
+        Some(moves) if moves > 0 => format!("mate in {}", moves),
+        Some(moves) => format!("mated in {}", -moves),
+        None => format!("{:+.2}", score as f64 / 100.0),
     }
+}
 
-    if move_count % 8 != 0 {
-        println!();
+fn print_game_status(game: &Game) {
+    let board = game.board();
+    let status = get_game_status(board);
+    match status {
+        GameStatus::Check => println!("Check!"),
+        GameStatus::Checkmate => println!("Checkmate! Game over."),
+        GameStatus::Stalemate => println!("Stalemate! Game ends in a draw."),
+        GameStatus::Draw(reason) => println!("Draw by {}! Game over.", reason),
+        GameStatus::Ongoing => {}
+    }
+
+    if !game.is_over() {
+        if let Some(reason) = get_claimable_draw(board) {
+            println!("A draw by {} can be claimed with 'claim'.", reason);
+        }
+        if game.draw_offer() == Some(board.side_to_move.opposite()) {
+            println!(
+                "{} offers a draw. Type 'accept' or 'decline', or just move.",
+                board.side_to_move.opposite()
+            );
+        }
     }
 }
 
-fn get_legal_moves_list(board: &Board, square: usize) -> Vec<usize> {
+fn print_legal_moves(board: &Board, square: Square) {
     let moves = board.get_legal_moves(square);
-    let mut result = Vec::new();
+    if moves.is_empty() {
+        println!("No legal moves for piece at {}", square);
+        return;
+    }
 
-    for i in 0..64 {
-        if (moves & (1u64 << i)) != 0 {
-            result.push(i);
+    let piece_info = board.piece_at(square).map_or("No piece", piece_name);
+
+    println!("Legal moves for {} at {}:", piece_info, square);
+
+    for (i, to) in moves.iter().enumerate() {
+        print!("{}) {} ", i + 1, to);
+        if (i + 1).is_multiple_of(8) {
+            println!();
         }
     }
 
-    result
+    if !moves.count().is_multiple_of(8) {
+        println!();
+    }
+}
+
+fn save_game(game: &Game, path: &str) {
+    match fs::write(path, game.to_pgn().to_string()) {
+        Ok(_) => println!("Saved game to {}", path),
+        Err(err) => println!("Error: could not write {}: {}", path, err),
+    }
+}
+
+fn load_game(path: &str) -> Option<Game> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            println!("Error: could not read {}: {}", path, err);
+            return None;
+        }
+    };
+
+    let games = match parse_pgn(&text) {
+        Ok(games) => games,
+        Err(err) => {
+            println!("Error: {}", err);
+            return None;
+        }
+    };
+
+    let game = match games.first() {
+        Some(game) => game,
+        None => {
+            println!("Error: {} contains no games", path);
+            return None;
+        }
+    };
+
+    match Game::from_pgn(game) {
+        Ok(game) => {
+            if games.len() > 1 {
+                println!("Loaded the first of {} games from {}", games.len(), path);
+            } else {
+                println!("Loaded game from {}", path);
+            }
+            Some(game)
+        }
+        Err(err) => {
+            println!("Error: {}", err);
+            None
+        }
+    }
 }
 
 fn print_help() {
     println!("\nAvailable commands:");
     println!("  e2e4       - Move a piece from e2 to e4");
+    println!("  e7e8q      - Move a pawn from e7 to e8 and promote it (q, r, b or n)");
+    println!("  e1h1       - In Chess960, castle by moving the king onto its own rook");
+    println!("               (e1g1 also works when the king has no other move there)");
+    println!("  Nf3, exd5  - Play a move in standard algebraic notation (O-O, e8=Q, ...)");
     println!("  e2         - Show legal moves from square e2 and select by number");
     println!("  legal e2   - Show legal moves from square e2");
+    println!("  undo       - Take back the last move");
+    println!("  draw       - Offer your opponent a draw, then make your move");
+    println!("  accept     - Accept your opponent's draw offer (or 'decline')");
+    println!("  claim      - Claim a draw by threefold repetition or the fifty-move rule");
+    println!("  resign     - Resign the game");
+    println!("  save game.pgn - Save the game so far as PGN");
+    println!("  load game.pgn - Resume the first game in a PGN file");
     println!("  print      - Display the current board");
+    println!("  go         - Let the engine play a move for the side to move");
+    println!("  eval       - Break down the static evaluation for the side to move");
     println!("  help       - Show this help message");
     println!("  quit/exit  - Exit the program\n");
 }
 
-fn run_interactive_mode() {
-    let mut board = Board::new();
+fn starting_board(variant: Variant, position: Option<u32>) -> Board {
+    match variant {
+        Variant::Standard => {
+            if position.is_some() {
+                eprintln!("--position only applies to --variant chess960");
+                process::exit(2);
+            }
+            Board::new()
+        }
+        Variant::Chess960 => {
+            let index = position.unwrap_or_else(chess960::random_index);
+            match chess960::start_position(index) {
+                Some(board) => {
+                    println!("Chess960 position {}", index);
+                    board
+                }
+                None => {
+                    eprintln!(
+                        "Chess960 positions are numbered 0 to {}",
+                        chess960::POSITION_COUNT - 1
+                    );
+                    process::exit(2);
+                }
+            }
+        }
+    }
+}
+
+fn run_interactive_mode(board: Board) {
+    let mut game = Game::from_board(board);
+    let mut searcher = Searcher::new(SearchLimits {
+        depth: Some(ENGINE_DEPTH),
+        time: Some(ENGINE_TIME),
+        ..SearchLimits::default()
+    });
 
     println!("\n=== Welcome to Crazy Chess! ===\n");
     println!("A bitboard-based chess engine with an interactive CLI");
     println!("Type 'help' for a list of commands");
 
-    board.print();
+    game.board().print();
 
     loop {
-        let side = match board.side_to_move {
+        let side_to_move = game.board().side_to_move;
+        let side = match side_to_move {
             Color::White => "White (W)",
             Color::Black => "Black (B)",
         };
@@ -138,7 +448,8 @@ fn run_interactive_mode() {
 
         let mut input = String::new();
         io::stdin().read_line(&mut input).unwrap();
-        let input = input.trim().to_lowercase();
+        let raw_input = input.trim();
+        let input = raw_input.to_lowercase();
 
         if input == "quit" || input == "exit" {
             println!("Thanks for playing!");
@@ -150,15 +461,91 @@ fn run_interactive_mode() {
             continue;
         }
 
+        if input == "undo" {
+            match game.undo_move() {
+                Ok(mv) => {
+                    println!("Took back {}", mv);
+                    game.board().print();
+                }
+                Err(err) => display_game_error(err),
+            }
+            continue;
+        }
+
+        if input.starts_with("save ") {
+            save_game(&game, raw_input[5..].trim());
+            continue;
+        }
+
+        if input.starts_with("load ") {
+            if let Some(loaded) = load_game(raw_input[5..].trim()) {
+                game = loaded;
+                print_game_status(&game);
+                game.board().print();
+            }
+            continue;
+        }
+
+        if input == "resign" {
+            match game.resign(side_to_move) {
+                Ok(_) => println!("{} resigns. Game over: {}", side_to_move, game.result()),
+                Err(err) => display_game_error(err),
+            }
+            continue;
+        }
+
+        if input == "draw" {
+            match game.offer_draw(side_to_move) {
+                Ok(_) => println!("{} offers a draw. Now make your move.", side_to_move),
+                Err(err) => display_game_error(err),
+            }
+            continue;
+        }
+
+        if input == "accept" {
+            match game.accept_draw(side_to_move) {
+                Ok(_) => println!("Draw agreed. Game over: {}", game.result()),
+                Err(err) => display_game_error(err),
+            }
+            continue;
+        }
+
+        if input == "decline" {
+            match game.decline_draw(side_to_move) {
+                Ok(_) => println!("Draw offer declined"),
+                Err(err) => display_game_error(err),
+            }
+            continue;
+        }
+
+        if input == "claim" {
+            match game.claim_draw() {
+                Ok(reason) => println!("Draw by {} claimed. Game over: {}", reason, game.result()),
+                Err(err) => display_game_error(err),
+            }
+            continue;
+        }
+
         if input == "print" {
-            board.print();
+            game.board().print();
+            continue;
+        }
+
+        if input == "go" {
+            play_engine_move(&mut game, &mut searcher);
+            continue;
+        }
+
+        if input == "eval" {
+            println!("Evaluation for {} in centipawns:", side_to_move);
+            println!("{}", eval::breakdown(game.board()));
             continue;
         }
 
         if input.starts_with("legal ") {
             if let Some(square_str) = input.strip_prefix("legal ") {
-                if let Some(square) = algebraic_to_index(square_str) {
-                    print_legal_moves(&board, square);
+                if let Some(square) = Square::from_algebraic(square_str) {
+                    print_legal_moves(game.board(), square);
                 } else {
                     println!(
                         "Invalid square notation: {}. Use format like 'e2'",
@@ -169,60 +556,46 @@ fn run_interactive_mode() {
             continue;
         }
 
-        if input.len() == 4 {
-            let from_str = &input[0..2];
-            let to_str = &input[2..4];
-
-            match (algebraic_to_index(from_str), algebraic_to_index(to_str)) {
-                (Some(from), Some(to)) => match board.make_move(from, to) {
-                    Ok(_) => {
-                        println!("Moved from {} to {}", from_str, to_str);
-
-                        match get_game_status(&board) {
-                            GameStatus::Check => println!("Check!"),
-                            GameStatus::Checkmate => println!("Checkmate! Game over."),
-                            GameStatus::Stalemate => println!("Stalemate! Game ends in a draw."),
-                            _ => {}
-                        }
-
-                        board.print();
+        if let Some((from, to, symbol)) = parse_coordinates(&input) {
+            let promotion = match symbol {
+                Some(symbol) => match char_to_promotion(symbol) {
+                    Some(piece_type) => Some(piece_type),
+                    None => {
+                        println!("Invalid promotion piece. Use q, r, b or n, e.g. 'e7e8q'");
+                        continue;
                     }
-                    Err(err) => display_move_error(err),
                 },
-                _ => println!("Invalid move notation. Use format like 'e2e4'"),
+                None => None,
+            };
+
+            match make_move(&mut game, from, to, promotion) {
+                Ok(_) => {
+                    println!("Moved from {} to {}", from, to);
+
+                    print_game_status(&game);
+                    game.board().print();
+                }
+                Err(err) => display_game_error(err),
             }
         } else if input.len() == 2 {
-            if let Some(from) = algebraic_to_index(&input) {
-                if board.get_piece_type_at(from).is_none() {
-                    println!("No piece at {}", input);
-                    continue;
-                }
+            if let Some(from) = Square::from_algebraic(&input) {
+                let piece = match game.board().piece_at(from) {
+                    Some(piece) if piece.color == side_to_move => piece,
+                    _ => {
+                        play_san(&mut game, raw_input);
+                        continue;
+                    }
+                };
 
-                let legal_moves = get_legal_moves_list(&board, from);
+                let legal_moves: Vec<Square> = game.board().get_legal_moves(from).iter().collect();
                 if legal_moves.is_empty() {
                     println!("No legal moves for piece at {}", input);
                     continue;
                 }
 
-                let piece_info = match board.get_piece_type_at(from) {
-                    Some((PieceType::Pawn, Color::White)) => "White Pawn",
-                    Some((PieceType::Pawn, Color::Black)) => "Black Pawn",
-                    Some((PieceType::Knight, Color::White)) => "White Knight",
-                    Some((PieceType::Knight, Color::Black)) => "Black Knight",
-                    Some((PieceType::Bishop, Color::White)) => "White Bishop",
-                    Some((PieceType::Bishop, Color::Black)) => "Black Bishop",
-                    Some((PieceType::Rook, Color::White)) => "White Rook",
-                    Some((PieceType::Rook, Color::Black)) => "Black Rook",
-                    Some((PieceType::King, Color::White)) => "White King",
-                    Some((PieceType::King, Color::Black)) => "Black King",
-                    Some((PieceType::Queen, Color::White)) => "White Queen",
-                    Some((PieceType::Queen, Color::Black)) => "Black Queen",
-                    _ => "Unknown piece",
-                };
-
-                println!("Legal moves for {} at {}:", piece_info, input);
-                for (i, &to) in legal_moves.iter().enumerate() {
-                    println!("  {}. {}", i + 1, index_to_algebraic(to));
+                println!("Legal moves for {} at {}:", piece_name(piece), input);
+                for (i, to) in legal_moves.iter().enumerate() {
+                    println!("  {}. {}", i + 1, to);
                 }
 
                 print!("Select move number (or 0 to cancel): ");
@@ -240,12 +613,24 @@ fn run_interactive_mode() {
 
                     if num <= legal_moves.len() {
                         let to = legal_moves[num - 1];
-                        match board.make_move(from, to) {
+                        let result = if is_promotion_move(game.board(), from, to) {
+                            match read_promotion_choice() {
+                                Some(piece_type) => {
+                                    make_move(&mut game, from, to, Some(piece_type))
+                                }
+                                None => Err(GameError::IllegalMove(MoveError::InvalidPromotion)),
+                            }
+                        } else {
+                            make_move(&mut game, from, to, None)
+                        };
+
+                        match result {
                             Ok(_) => {
-                                println!("Moved from {} to {}", input, index_to_algebraic(to));
-                                board.print();
+                                println!("Moved from {} to {}", input, to);
+                                print_game_status(&game);
+                                game.board().print();
                             }
-                            Err(err) => display_move_error(err),
+                            Err(err) => display_game_error(err),
                         }
                     } else {
                         println!("Invalid move number");
@@ -256,22 +641,137 @@ fn run_interactive_mode() {
             } else {
                 println!("Invalid square notation. Use format like 'e2'");
             }
-        } else {
+        } else if raw_input.is_empty() {
             println!("Unrecognized command. Type 'help' for available commands");
+        } else {
+            play_san(&mut game, raw_input);
         }
     }
 }
 
+fn run_perft(fen: &str, depth: u32) {
+    let fen = if fen == "startpos" { STARTING_FEN } else { fen };
+    let mut board = match Board::from_fen(fen) {
+        Ok(board) => board,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        }
+    };
+
+    let start = Instant::now();
+    let results = perft::divide(&mut board, depth);
+    let elapsed = start.elapsed();
+
+    for (mv, nodes) in &results {
+        println!("{}: {}", mv, nodes);
+    }
+
+    let total: u64 = if depth == 0 {
+        1
+    } else {
+        results.iter().map(|(_, nodes)| nodes).sum()
+    };
+    println!();
+    println!("Nodes searched: {}", total);
+    println!(
+        "Time: {:.3}s ({:.0} nodes/s)",
+        elapsed.as_secs_f64(),
+        total as f64 / elapsed.as_secs_f64().max(1e-9)
+    );
+}
+
 fn main() {
     let args = Cli::parse();
 
-    if args.interactive {
-        run_interactive_mode();
+    if let Some(Command::Perft { fen, depth }) = args.command {
+        run_perft(&fen, depth);
+    } else if args.uci {
+        if let Err(err) = uci::run(io::stdin().lock(), io::stdout()) {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        }
+    } else if args.interactive {
+        run_interactive_mode(starting_board(args.variant, args.position));
     } else {
+        let board = starting_board(args.variant, args.position);
         println!("Starting with a new board:");
-        let board = Board::new();
         board.print();
         println!("\nUse --interactive flag to play the game");
         println!("Example: cargo run -p cli-chess -- --interactive");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coordinate_input() {
+        assert_eq!(
+            parse_coordinates("e2e4"),
+            Some((Square::E2, Square::E4, None))
+        );
+        assert_eq!(
+            parse_coordinates("e7e8q"),
+            Some((Square::E7, Square::E8, Some('q')))
+        );
+        // A bad promotion letter is still coordinate input, and gets its own error.
+        assert_eq!(
+            parse_coordinates("e7e8x"),
+            Some((Square::E7, Square::E8, Some('x')))
+        );
+        assert_eq!(parse_coordinates("e2e4e5"), None);
+        assert_eq!(parse_coordinates("é2e4"), None);
+    }
+
+    #[test]
+    fn test_chess960_castling_by_king_destination() {
+        let board = Board::from_fen("1k6/8/8/8/8/8/8/RK2R3 w EA - 0 1").unwrap();
+        assert!(board.chess960);
+
+        assert_eq!(
+            chess960_castling(&board, Square::B1, Square::G1),
+            Some(Square::E1)
+        );
+        // Kc1 is an ordinary king move, even though queenside castling
+        // would also put the king there.
+        assert_eq!(chess960_castling(&board, Square::B1, Square::C1), None);
+        assert_eq!(chess960_castling(&board, Square::B1, Square::C2), None);
+
+        // With the king on f1, f1g1 is a plain king step and stays one.
+        let board = Board::from_fen("1r3kr1/8/8/8/8/8/8/1R3KR1 w GBgb - 0 1").unwrap();
+        assert_eq!(chess960_castling(&board, Square::F1, Square::G1), None);
+        assert_eq!(
+            chess960_castling(&board, Square::F1, Square::C1),
+            Some(Square::B1)
+        );
+
+        assert_eq!(
+            chess960_castling(&Board::new(), Square::E1, Square::G1),
+            None
+        );
+    }
+
+    #[test]
+    fn test_five_character_san_is_not_coordinate_input() {
+        let cases = [
+            (
+                "rnbqkbnr/ppp2ppp/3p4/4p3/2B1P3/8/PPPP1PPP/RNBQK1NR w KQkq - 0 3",
+                "Bxf7+",
+            ),
+            (
+                "r3kbnr/pppqpppp/2n5/3p1b2/3P1B2/2N5/PPPQPPPP/R3KBNR w KQkq - 6 5",
+                "O-O-O",
+            ),
+            ("4k3/8/8/8/8/5N2/3p4/1N2K3 w - - 0 1", "Nbxd2"),
+            ("3k4/8/8/8/8/8/6K1/R2q3R w - - 0 1", "Raxd1"),
+        ];
+
+        for (fen, san) in cases {
+            assert_eq!(parse_coordinates(&san.to_lowercase()), None, "{}", san);
+            let board = Board::from_fen(fen).unwrap();
+            assert!(parse_san(&board, san).is_ok(), "{}", san);
+        }
+    }
+}
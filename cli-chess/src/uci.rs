@@ -0,0 +1,536 @@
+use chess_engine::{
+    board::Board,
+    movement::moves::Move,
+    pieces::piece_type::Color,
+    search::{tt::DEFAULT_SIZE_MB, SearchLimits, SearchResult, Searcher},
+};
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const MAX_HASH_MB: usize = 4096;
+
+// Kept back from the clock for the time it takes the GUI to hear about the move.
+const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+
+// How many moves the remaining time is spread over when the GUI does not
+// send movestogo.
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct GoOptions {
+    limits: SearchLimits,
+    // Search until told to stop, without answering early.
+    infinite: bool,
+}
+
+struct RunningSearch {
+    handle: JoinHandle<Searcher>,
+    stop: Arc<AtomicBool>,
+    infinite: bool,
+}
+
+struct Engine<W> {
+    output: Arc<Mutex<W>>,
+    board: Board,
+    chess960: bool,
+    // Handed to the search thread while it runs and given back when it ends.
+    searcher: Option<Searcher>,
+    running: Option<RunningSearch>,
+}
+
+// Speaks UCI until `quit` or the end of the input, and hands the output back.
+// At the end of the input a search with limits is allowed to finish, so that
+// a piped script ending in `go depth 5` still gets its move.
+pub fn run<R, W>(input: R, output: W) -> io::Result<W>
+where
+    R: BufRead,
+    W: Write + Send + 'static,
+{
+    let mut engine = Engine {
+        output: Arc::new(Mutex::new(output)),
+        board: Board::new(),
+        chess960: false,
+        searcher: Some(Searcher::new(SearchLimits::default())),
+        running: None,
+    };
+
+    for line in input.lines() {
+        if !engine.handle(&line?)? {
+            break;
+        }
+    }
+    engine.finish_search();
+
+    let output = Arc::try_unwrap(engine.output)
+        .ok()
+        .expect("the search thread has finished");
+    Ok(output.into_inner().unwrap_or_else(PoisonError::into_inner))
+}
+
+impl<W: Write + Send + 'static> Engine<W> {
+    // Returns false once the engine should exit.
+    fn handle(&mut self, line: &str) -> io::Result<bool> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let (command, args) = match tokens.split_first() {
+            Some((&command, args)) => (command, args),
+            None => return Ok(true),
+        };
+
+        match command {
+            "uci" => {
+                self.send(&format!(
+                    "id name Crazy Chess {}",
+                    env!("CARGO_PKG_VERSION")
+                ))?;
+                self.send("id author the Crazy Chess contributors")?;
+                self.send(&format!(
+                    "option name Hash type spin default {} min 1 max {}",
+                    DEFAULT_SIZE_MB, MAX_HASH_MB
+                ))?;
+                self.send("option name Clear Hash type button")?;
+                self.send("option name UCI_Chess960 type check default false")?;
+                self.send("uciok")?;
+            }
+            "isready" => self.send("readyok")?,
+            "ucinewgame" => {
+                self.board = Board::new();
+                self.searcher().tt_mut().clear();
+            }
+            "position" => match parse_position(args, self.chess960) {
+                Ok(board) => {
+                    self.finish_search();
+                    self.board = board;
+                }
+                Err(err) => self.send(&format!("info string {}", err))?,
+            },
+            "go" => {
+                let go = parse_go(args, self.board.side_to_move);
+                self.start_search(go);
+            }
+            "stop" => self.stop_search(),
+            "setoption" => self.set_option(args)?,
+            // A scripted `go depth` still gets its answer; `go infinite` is
+            // stopped.
+            "quit" => {
+                self.finish_search();
+                return Ok(false);
+            }
+            "debug" | "register" | "ponderhit" => {}
+            _ => self.send(&format!("info string unknown command {}", command))?,
+        }
+        Ok(true)
+    }
+
+    fn set_option(&mut self, args: &[&str]) -> io::Result<()> {
+        // setoption name <id, possibly several words> [value <x>]
+        let value_at = args
+            .iter()
+            .position(|&token| token == "value")
+            .unwrap_or(args.len());
+        let name = args.get(1..value_at).unwrap_or_default().join(" ");
+        let value = args.get(value_at + 1..).unwrap_or_default().join(" ");
+
+        match name.to_lowercase().as_str() {
+            "hash" => match value.parse::<usize>() {
+                Ok(size_mb) if (1..=MAX_HASH_MB).contains(&size_mb) => {
+                    self.searcher().tt_mut().resize(size_mb)
+                }
+                _ => self.send(&format!(
+                    "info string Hash must be between 1 and {} MB",
+                    MAX_HASH_MB
+                ))?,
+            },
+            "clear hash" => self.searcher().tt_mut().clear(),
+            "uci_chess960" => self.chess960 = value.eq_ignore_ascii_case("true"),
+            _ => self.send(&format!("info string unknown option {}", name))?,
+        }
+        Ok(())
+    }
+
+    fn start_search(&mut self, go: GoOptions) {
+        self.finish_search();
+        let mut searcher = self.searcher.take().expect("no search is running");
+        searcher.set_limits(go.limits);
+        let stop = searcher.stop_flag();
+        stop.store(false, Ordering::Relaxed);
+
+        let output = Arc::clone(&self.output);
+        let board = self.board.clone();
+        let thread_stop = Arc::clone(&stop);
+        let handle = thread::spawn(move || {
+            // Nothing useful can be done about a GUI that stopped listening.
+            let result = searcher.search_with_info(&board, |searcher, result| {
+                send(&output, &info_line(searcher, result)).ok();
+            });
+
+            // An infinite search may run out of depth, or find a mate, long
+            // before the GUI wants its answer.
+            if go.infinite {
+                while !thread_stop.load(Ordering::Relaxed) {
+                    thread::park();
+                }
+            }
+
+            let best_move = result
+                .best_move
+                .map_or_else(|| "0000".to_string(), |mv| mv.to_string());
+            send(&output, &format!("bestmove {}", best_move)).ok();
+            searcher
+        });
+
+        self.running = Some(RunningSearch {
+            handle,
+            stop,
+            infinite: go.infinite,
+        });
+    }
+
+    fn stop_search(&mut self) {
+        if let Some(running) = &self.running {
+            running.stop.store(true, Ordering::Relaxed);
+            running.handle.thread().unpark();
+        }
+        self.finish_search();
+    }
+
+    // Waits for a running search to end by itself, stopping it first if it
+    // never would.
+    fn finish_search(&mut self) {
+        if let Some(running) = self.running.take() {
+            if running.infinite {
+                running.stop.store(true, Ordering::Relaxed);
+                running.handle.thread().unpark();
+            }
+            let searcher = running.handle.join().expect("the search thread panicked");
+            self.searcher = Some(searcher);
+        }
+    }
+
+    fn searcher(&mut self) -> &mut Searcher {
+        self.finish_search();
+        self.searcher.as_mut().expect("no search is running")
+    }
+
+    fn send(&self, line: &str) -> io::Result<()> {
+        send(&self.output, line)
+    }
+}
+
+fn send<W: Write>(output: &Mutex<W>, line: &str) -> io::Result<()> {
+    let mut output = output.lock().unwrap_or_else(PoisonError::into_inner);
+    writeln!(output, "{}", line)?;
+    output.flush()
+}
+
+fn parse_position(args: &[&str], chess960: bool) -> Result<Board, String> {
+    let moves_at = args
+        .iter()
+        .position(|&token| token == "moves")
+        .unwrap_or(args.len());
+    let mut board = match args.first() {
+        Some(&"startpos") => Board::new(),
+        Some(&"fen") => {
+            Board::from_fen(&args[1..moves_at].join(" ")).map_err(|err| err.to_string())?
+        }
+        _ => return Err("position needs 'startpos' or 'fen'".to_string()),
+    };
+    // Chess960 castling is sent as the king taking its own rook, which is
+    // how the board encodes it in that mode.
+    board.chess960 |= chess960;
+
+    for &text in args.iter().skip(moves_at + 1) {
+        let mv = board
+            .generate_moves()
+            .iter()
+            .copied()
+            .find(|mv| mv.to_string() == text)
+            .ok_or_else(|| format!("illegal move {} in position command", text))?;
+        board.play_move(mv).expect("generated moves are legal");
+    }
+    Ok(board)
+}
+
+fn parse_go(args: &[&str], side_to_move: Color) -> GoOptions {
+    let mut go = GoOptions::default();
+    let mut time_left = None;
+    let mut increment = Duration::ZERO;
+    let mut moves_to_go = None;
+
+    let mut tokens = args.iter().copied();
+    while let Some(token) = tokens.next() {
+        let ours = match side_to_move {
+            Color::White => token.starts_with('w'),
+            Color::Black => token.starts_with('b'),
+        };
+        match token {
+            "depth" => go.limits.depth = number(&mut tokens).map(|depth| depth as u32),
+            "nodes" => go.limits.nodes = number(&mut tokens),
+            "movetime" => go.limits.time = number(&mut tokens).map(Duration::from_millis),
+            "wtime" | "btime" => {
+                let time = number(&mut tokens).map(Duration::from_millis);
+                if ours {
+                    time_left = time;
+                }
+            }
+            "winc" | "binc" => {
+                let time = number(&mut tokens).map(Duration::from_millis);
+                if ours {
+                    increment = time.unwrap_or_default();
+                }
+            }
+            "movestogo" => moves_to_go = number(&mut tokens),
+            "infinite" => go.infinite = true,
+            _ => {}
+        }
+    }
+
+    if go.infinite {
+        go.limits = SearchLimits::default();
+    } else if let (Some(time_left), None) = (time_left, go.limits.time) {
+        go.limits.time = Some(time_budget(time_left, increment, moves_to_go));
+    }
+    go
+}
+
+// Some GUIs send negative times once a clock runs out; treat those as zero.
+fn number<'a>(tokens: &mut impl Iterator<Item = &'a str>) -> Option<u64> {
+    tokens
+        .next()
+        .and_then(|token| token.parse::<i64>().ok())
+        .map(|value| value.max(0) as u64)
+}
+
+fn time_budget(time_left: Duration, increment: Duration, moves_to_go: Option<u64>) -> Duration {
+    let usable = time_left.saturating_sub(MOVE_OVERHEAD);
+    let moves = moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1) as u32;
+    (usable / moves + increment * 3 / 4).min(usable)
+}
+
+fn info_line(searcher: &Searcher, result: &SearchResult) -> String {
+    let score = match result.mate_in() {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", result.score),
+    };
+    let millis = result.elapsed.as_millis() as u64;
+
+    let mut line = format!(
+        "info depth {} score {} nodes {} time {} nps {} hashfull {}",
+        result.depth,
+        score,
+        result.nodes,
+        millis,
+        result.nodes * 1000 / millis.max(1),
+        searcher.tt().hashfull()
+    );
+    if !result.pv.is_empty() {
+        let pv: Vec<String> = result.pv.iter().map(Move::to_string).collect();
+        line.push_str(" pv ");
+        line.push_str(&pv.join(" "));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(script: &str) -> Vec<String> {
+        let output = run(script.as_bytes(), Vec::new()).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn best_moves(lines: &[String]) -> Vec<&str> {
+        lines
+            .iter()
+            .filter_map(|line| line.strip_prefix("bestmove "))
+            .collect()
+    }
+
+    // A writer the test can read while the engine still owns it.
+    #[derive(Clone, Default)]
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedOutput {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_handshake() {
+        let lines = session("uci\nisready\nquit\n");
+
+        assert!(lines[0].starts_with("id name Crazy Chess"));
+        assert!(lines.contains(&"option name Hash type spin default 16 min 1 max 4096".into()));
+        assert!(lines.contains(&"option name Clear Hash type button".into()));
+        assert_eq!(lines[lines.len() - 2..], ["uciok", "readyok"]);
+    }
+
+    #[test]
+    fn test_search_after_moves_reports_each_depth() {
+        let lines = session("position startpos moves e2e4 e7e5 g1f3\ngo depth 3\n");
+
+        let depths: Vec<&str> = lines
+            .iter()
+            .filter_map(|line| line.strip_prefix("info depth "))
+            .map(|rest| rest.split(' ').next().unwrap())
+            .collect();
+        assert_eq!(depths, ["1", "2", "3"]);
+        assert!(lines[0].contains(" score cp ") && lines[0].contains(" pv "));
+        assert!(lines[0].contains(" nodes ") && lines[0].contains(" hashfull "));
+
+        let board = parse_position(&["startpos", "moves", "e2e4", "e7e5", "g1f3"], false).unwrap();
+        assert_eq!(board.side_to_move, Color::Black);
+        let best = best_moves(&lines);
+        assert_eq!(best.len(), 1);
+        assert!(board
+            .generate_moves()
+            .iter()
+            .any(|mv| mv.to_string() == best[0]));
+    }
+
+    #[test]
+    fn test_finds_mate_from_fen() {
+        let lines = session("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 2\nquit\n");
+
+        assert!(lines.iter().any(|line| line.contains("score mate 1 ")));
+        assert_eq!(best_moves(&lines), ["a1a8"]);
+    }
+
+    #[test]
+    fn test_positions_without_legal_moves_and_bad_input() {
+        let lines = session(concat!(
+            "position fen 7k/6Q1/6K1/8/8/8/8/8 b - - 0 1\n",
+            "go depth 2\n",
+            "position startpos moves e2e5\n",
+            "position fen not a fen\n",
+            "setoption name Hash value 0\n",
+            "setoption name Contempt value 20\n",
+            "frobnicate\n",
+        ));
+
+        assert_eq!(best_moves(&lines), ["0000"]);
+        let notes: Vec<&String> = lines
+            .iter()
+            .filter(|line| line.starts_with("info string"))
+            .collect();
+        assert_eq!(notes.len(), 5);
+        assert!(notes[0].contains("e2e5"));
+        assert!(notes[4].contains("frobnicate"));
+    }
+
+    #[test]
+    fn test_options() {
+        let lines = session(concat!(
+            "setoption name Hash value 1\n",
+            "setoption name Clear Hash\n",
+            "setoption name UCI_Chess960 value true\n",
+            "position fen bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9\n",
+            "go depth 2\n",
+        ));
+
+        assert!(lines.iter().all(|line| !line.starts_with("info string")));
+        assert_eq!(best_moves(&lines).len(), 1);
+    }
+
+    #[test]
+    fn test_clock_and_node_limits() {
+        let go = parse_go(
+            &["wtime", "60000", "btime", "1000", "winc", "400"],
+            Color::Black,
+        );
+        assert_eq!(
+            go.limits.time,
+            Some(time_budget(Duration::from_secs(1), Duration::ZERO, None))
+        );
+
+        let go = parse_go(&["wtime", "-20", "movestogo", "5"], Color::White);
+        assert_eq!(go.limits.time, Some(Duration::ZERO));
+
+        let go = parse_go(
+            &["btime", "9000", "movetime", "250", "nodes", "100"],
+            Color::Black,
+        );
+        assert_eq!(go.limits.time, Some(Duration::from_millis(250)));
+        assert_eq!(go.limits.nodes, Some(100));
+
+        let go = parse_go(&["infinite", "depth", "3"], Color::White);
+        assert!(go.infinite);
+        assert_eq!(go.limits, SearchLimits::default());
+
+        assert_eq!(
+            time_budget(
+                Duration::from_millis(3_050),
+                Duration::from_millis(400),
+                None
+            ),
+            Duration::from_millis(100 + 300)
+        );
+        assert_eq!(
+            time_budget(Duration::from_millis(250), Duration::from_secs(2), Some(1)),
+            Duration::from_millis(200)
+        );
+
+        let lines = session("position startpos\ngo wtime 2000 btime 2000 winc 0 binc 0\n");
+        assert_eq!(best_moves(&lines).len(), 1);
+    }
+
+    #[test]
+    fn test_quit_stops_an_infinite_search() {
+        let lines = session("position startpos\ngo infinite\nquit\n");
+
+        assert_eq!(best_moves(&lines).len(), 1);
+        assert!(lines.last().unwrap().starts_with("bestmove "));
+    }
+
+    #[test]
+    fn test_infinite_search_waits_for_stop() {
+        let (reader, mut writer) = io::pipe().unwrap();
+        let output = SharedOutput::default();
+        let engine = {
+            let output = output.clone();
+            thread::spawn(move || run(io::BufReader::new(reader), output))
+        };
+
+        // Only two kings: every depth is instant, so the search soon runs out
+        // of plies and has to wait.
+        writeln!(writer, "position fen 8/8/8/4k3/8/8/8/4K3 w - - 0 1").unwrap();
+        writeln!(writer, "go infinite").unwrap();
+        writeln!(writer, "isready").unwrap();
+        thread::sleep(Duration::from_millis(200));
+        let before_stop = output.text();
+        assert!(before_stop.contains("readyok"));
+        assert!(before_stop.contains("info depth 1 "));
+        assert!(!before_stop.contains("bestmove"));
+
+        writeln!(writer, "stop").unwrap();
+        writeln!(writer, "quit").unwrap();
+        engine.join().unwrap().unwrap();
+
+        let text = output.text();
+        assert_eq!(text.matches("bestmove").count(), 1);
+        assert!(text
+            .trim_end()
+            .lines()
+            .last()
+            .unwrap()
+            .starts_with("bestmove e1"));
+    }
+}